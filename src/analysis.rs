@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use rocket::tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use chess_engine::evaluator::EvalParams;
+use chess_engine::position::Position;
+use chess_engine::search::{SearchInfo, SearchLimits, SearchOptions, Searcher, TimeControl, TimeManager};
+
+/**
+ * Analyses currently running in the background, by id, so that they can be cancelled
+ */
+#[derive(Default)]
+pub struct Analyses {
+    next_id: AtomicU64,
+    running: Arc<Mutex<HashMap<u64, Arc<AtomicBool>>>>,
+}
+
+#[derive(Debug)]
+pub enum AnalysisEvent {
+    Info(SearchInfo),
+    /// the best move once the search has finished or been stopped, in long algebraic notation
+    BestMove(Option<String>),
+}
+
+/// Stops the search and forgets the analysis when dropped, eg: when the client disconnects
+pub struct AnalysisGuard {
+    pub id: u64,
+    stop: Arc<AtomicBool>,
+    running: Arc<Mutex<HashMap<u64, Arc<AtomicBool>>>>,
+}
+
+impl Drop for AnalysisGuard {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.running.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl Analyses {
+    /// Starts searching the position on its own thread, returning the search events as they happen
+    pub fn start(
+        &self,
+        position: Position,
+        limits: SearchLimits,
+        time: TimeControl,
+        params: EvalParams,
+        options: SearchOptions,
+    ) -> (AnalysisGuard, UnboundedReceiver<AnalysisEvent>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let stop = Arc::new(AtomicBool::new(false));
+        self.running.lock().unwrap().insert(id, stop.clone());
+
+        let (sender, receiver) = unbounded_channel();
+        let search_stop = stop.clone();
+
+        std::thread::spawn(move || {
+            let time = TimeManager::new(&time, position.active_colour);
+            let mut searcher = Searcher::new(search_stop.clone(), params).with_options(options);
+            let result = searcher.search(&position, &limits, time, |info| {
+                // nobody is listening anymore
+                if sender.send(AnalysisEvent::Info(info.clone())).is_err() {
+                    search_stop.store(true, Ordering::Relaxed);
+                }
+            });
+
+            let _ = sender.send(AnalysisEvent::BestMove(result.best_move.map(|mv| mv.to_uci())));
+        });
+
+        let guard = AnalysisGuard {
+            id,
+            stop,
+            running: self.running.clone(),
+        };
+
+        (guard, receiver)
+    }
+
+    /// Returns false if there is no running analysis with the id
+    pub fn stop(&self, id: u64) -> bool {
+        match self.running.lock().unwrap().get(&id) {
+            Some(stop) => {
+                stop.store(true, Ordering::Relaxed);
+                true
+            },
+            None => false,
+        }
+    }
+}
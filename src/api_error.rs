@@ -0,0 +1,113 @@
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use rocket::serde::json::{self, Json};
+use rocket::serde::Serialize;
+use rocket::serde::json::serde_json::error::Category;
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ErrorDetail {
+    pub code: &'static str,
+    pub message: String,
+    /// the request field that caused the error, if there is one
+    pub field: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+/**
+ * An error response with a JSON body of the form `{ "error": { "code", "message", "field" } }`
+ */
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: Status,
+    pub detail: ErrorDetail,
+}
+
+impl ApiError {
+    pub fn new(status: Status, code: &'static str, message: impl Into<String>) -> ApiError {
+        ApiError {
+            status,
+            detail: ErrorDetail {
+                code,
+                message: message.into(),
+                field: None,
+            },
+        }
+    }
+
+    pub fn with_field(mut self, field: impl Into<String>) -> ApiError {
+        self.detail.field = Some(field.into());
+        self
+    }
+
+    pub fn invalid_fen(message: String) -> ApiError {
+        ApiError::new(Status::UnprocessableEntity, "invalid_fen", message).with_field("fen")
+    }
+
+    pub fn invalid_move(index: usize, message: String) -> ApiError {
+        ApiError::new(Status::UnprocessableEntity, "invalid_move", message).with_field(format!("moves[{}]", index))
+    }
+
+    pub fn invalid_searchmove(index: usize, message: String) -> ApiError {
+        ApiError::new(Status::UnprocessableEntity, "invalid_move", message).with_field(format!("searchmoves[{}]", index))
+    }
+
+    pub fn invalid_multipv() -> ApiError {
+        ApiError::new(Status::UnprocessableEntity, "invalid_payload", "multipv must be at least 1").with_field("multipv")
+    }
+}
+
+/// JSON that can't be parsed is a bad request, JSON that doesn't fit the payload is unprocessable
+impl From<json::Error<'_>> for ApiError {
+    fn from(error: json::Error<'_>) -> ApiError {
+        match error {
+            json::Error::Io(e) => ApiError::new(Status::PayloadTooLarge, "payload_too_large", e.to_string()),
+            json::Error::Parse(_, e) => match e.classify() {
+                Category::Data => ApiError::new(Status::UnprocessableEntity, "invalid_payload", e.to_string()),
+                _ => ApiError::new(Status::BadRequest, "malformed_json", e.to_string()),
+            },
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        (self.status, Json(ErrorBody { error: self.detail })).respond_to(request)
+    }
+}
+
+#[catch(400)]
+pub fn bad_request() -> ApiError {
+    ApiError::new(Status::BadRequest, "bad_request", "The request could not be understood")
+}
+
+#[catch(404)]
+pub fn not_found(request: &Request) -> ApiError {
+    ApiError::new(Status::NotFound, "not_found", format!("No route for {} {}", request.method(), request.uri()))
+}
+
+#[catch(413)]
+pub fn payload_too_large() -> ApiError {
+    ApiError::new(Status::PayloadTooLarge, "payload_too_large", "The request body is too large")
+}
+
+#[catch(422)]
+pub fn unprocessable_entity() -> ApiError {
+    ApiError::new(Status::UnprocessableEntity, "unprocessable_entity", "The request was well-formed but invalid")
+}
+
+#[catch(500)]
+pub fn internal_error() -> ApiError {
+    ApiError::new(Status::InternalServerError, "internal_error", "Something went wrong evaluating the request")
+}
+
+#[catch(default)]
+pub fn default_catcher(status: Status, _request: &Request) -> ApiError {
+    ApiError::new(status, "error", status.reason_lossy())
+}
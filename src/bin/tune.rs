@@ -0,0 +1,104 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use chess_engine::evaluator::EvalParams;
+use chess_engine::search::Searcher;
+use chess_engine::tuner::{fit_scaling, load_positions, mean_squared_error, quiet_position, tune, LabelledPosition};
+
+const USAGE: &str = "Usage: tune <positions file> <output .json> [--params <start .json|.toml>] [--passes <n>] [--mirror]";
+
+struct Args {
+    positions: PathBuf,
+    output: PathBuf,
+    params: Option<PathBuf>,
+    passes: usize,
+    /// also tune on every position with the colours swapped
+    mirror: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = env::args().skip(1);
+    let mut files = vec![];
+    let mut params = None;
+    let mut passes = 100;
+    let mut mirror = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--params" => params = Some(PathBuf::from(args.next().ok_or("--params needs a file")?)),
+            "--passes" => passes = args.next()
+                .and_then(|n| n.parse().ok())
+                .ok_or("--passes needs a number")?,
+            "--mirror" => mirror = true,
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+
+    match <[PathBuf; 2]>::try_from(files) {
+        Ok([positions, output]) => Ok(Args { positions, output, params, passes, mirror }),
+        Err(_) => Err(String::from(USAGE)),
+    }
+}
+
+fn run() -> Result<(), String> {
+    let args = parse_args()?;
+
+    let params = match &args.params {
+        Some(path) => EvalParams::from_file(path)?,
+        None => EvalParams::default(),
+    };
+
+    let mut positions = load_positions(&args.positions)?;
+
+    if args.mirror {
+        let mirrored: Vec<_> = positions.iter()
+            .map(|labelled| LabelledPosition { position: labelled.position.mirrored(), result: 1.0 - labelled.result })
+            .collect();
+        positions.extend(mirrored);
+    }
+
+    println!("Loaded {} positions", positions.len());
+
+    // tune against the quiet positions at the end of each capture sequence, leaving out the mates
+    let mut searcher = Searcher::new(Arc::new(AtomicBool::new(false)), params.clone());
+    let positions: Vec<_> = positions.into_iter()
+        .filter_map(|labelled| Some(LabelledPosition {
+            position: quiet_position(&mut searcher, &labelled.position)?,
+            result: labelled.result,
+        }))
+        .collect();
+    println!("Tuning on {} quiet positions", positions.len());
+
+    let k = fit_scaling(&positions, &params);
+    println!("K = {:.3}, error = {:.6}", k, mean_squared_error(&positions, &params, k));
+
+    let tuned = tune(&positions, &params, k, args.passes, |pass, error, params| {
+        println!("Pass {}: error = {:.6}", pass, error);
+
+        // saved after every pass so a long run can be stopped at any point
+        if let Err(e) = write_params(&args.output, params) {
+            eprintln!("{}", e);
+        }
+    });
+
+    write_params(&args.output, &tuned)?;
+    println!("Wrote {}", args.output.display());
+
+    Ok(())
+}
+
+fn write_params(path: &PathBuf, params: &EvalParams) -> Result<(), String> {
+    let json = rocket::serde::json::to_pretty_string(params).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Can't write {}: {}", path.display(), e))
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,344 @@
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use chess_engine::book::{BookOptions, BookSelection, OpeningBook};
+use chess_engine::board_navigator::{make_move, parse_uci_move};
+use chess_engine::evaluator::EvalParams;
+use chess_engine::fen::try_from_fen;
+use chess_engine::position::Position;
+use chess_engine::search::{SearchInfo, SearchLimits, SearchOptions, Searcher, TimeControl, TimeManager, MAX_DEPTH};
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+const MAX_THREADS: usize = 256;
+const MAX_MULTI_PV: usize = 256;
+const MAX_BOOK_DEPTH: usize = 200;
+
+/// Everything `go` can be followed by, which ends a list of `searchmoves`
+const GO_KEYWORDS: [&str; 12] = [
+    "searchmoves", "ponder", "wtime", "btime", "winc", "binc", "movestogo", "depth", "nodes", "mate", "movetime", "infinite",
+];
+
+/**
+ * The engine's side of the UCI protocol, read from stdin and answered on stdout
+ */
+struct Uci {
+    position: Position,
+    options: SearchOptions,
+    params: EvalParams,
+    book: OpeningBook,
+    /// whether moves are played from the book, when there is one
+    own_book: bool,
+    /// the running search, its stop flag and the sender dropped to release an infinite search's best move
+    search: Option<(JoinHandle<()>, Arc<AtomicBool>, Sender<()>)>,
+}
+
+impl Uci {
+    fn new() -> Uci {
+        Uci {
+            position: try_from_fen(START_FEN).unwrap(),
+            options: SearchOptions::default(),
+            params: EvalParams::default(),
+            book: OpeningBook::default(),
+            own_book: true,
+            search: None,
+        }
+    }
+
+    /// Returns false once the GUI has asked to quit
+    fn handle(&mut self, line: &str) -> Result<bool, String> {
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("uci") => {
+                println!("id name {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+                println!("option name Threads type spin default 1 min 1 max {}", MAX_THREADS);
+                println!("option name MultiPV type spin default 1 min 1 max {}", MAX_MULTI_PV);
+                println!("option name OwnBook type check default true");
+                println!("option name BookFile type string default <empty>");
+                println!("option name BookDepth type spin default {} min 0 max {}", BookOptions::default().depth, MAX_BOOK_DEPTH);
+                println!("option name BestBookMove type check default false");
+                println!("uciok");
+            },
+            Some("isready") => println!("readyok"),
+            Some("setoption") => self.set_option(&tokens.collect::<Vec<_>>())?,
+            Some("ucinewgame") => {
+                self.stop();
+                self.position = try_from_fen(START_FEN).unwrap();
+            },
+            Some("position") => {
+                self.stop();
+                self.position = parse_position(&tokens.collect::<Vec<_>>())?;
+            },
+            Some("go") => {
+                self.stop();
+                let (limits, time) = parse_go(&self.position, &tokens.collect::<Vec<_>>())?;
+                self.go(limits, time);
+            },
+            Some("stop") => self.stop(),
+            Some("quit") => {
+                self.stop();
+                return Ok(false);
+            },
+            Some(command) => return Err(format!("Unknown command: {}", command)),
+            None => {},
+        }
+
+        Ok(true)
+    }
+
+    /// `setoption name <name> value <value>`
+    fn set_option(&mut self, tokens: &[&str]) -> Result<(), String> {
+        let value_at = tokens.iter().position(|token| *token == "value");
+        let name = tokens[1.min(tokens.len())..value_at.unwrap_or(tokens.len())].join(" ");
+        let value = value_at.map(|i| tokens[i + 1..].join(" ")).unwrap_or_default();
+
+        let spin = |min: usize, max: usize| match value.parse::<usize>() {
+            Ok(value) if (min..=max).contains(&value) => Ok(value),
+            _ => Err(format!("Invalid value for {}: {}", name, value)),
+        };
+        let check = || match value.as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(format!("Invalid value for {}: {}", name, value)),
+        };
+
+        let mut book_options = self.book.options.clone();
+        match name.to_lowercase().as_str() {
+            "threads" => self.options.threads = spin(1, MAX_THREADS)?,
+            "multipv" => self.options.multi_pv = spin(1, MAX_MULTI_PV)?,
+            "ownbook" => self.own_book = check()?,
+            "bookfile" => book_options.file = (!value.is_empty() && value != "<empty>").then(|| value.clone().into()),
+            "bookdepth" => book_options.depth = spin(0, MAX_BOOK_DEPTH)?,
+            "bestbookmove" => book_options.selection = if check()? { BookSelection::Best } else { BookSelection::Weighted },
+            _ => return Err(format!("Unknown option: {}", name)),
+        }
+
+        // the book is only read again when it's a different file
+        if book_options.file != self.book.options.file {
+            self.book = OpeningBook::open(book_options)?;
+        } else {
+            self.book.options = book_options;
+        }
+
+        Ok(())
+    }
+
+    fn go(&mut self, limits: SearchLimits, time: TimeControl) {
+        // searching for a mate or until stopped is analysis, which the book has no part in
+        if self.own_book && limits.mate.is_none() && !limits.infinite {
+            let book_move = self.book.probe(&self.position)
+                .filter(|mv| limits.searchmoves.is_empty() || limits.searchmoves.contains(mv));
+
+            if let Some(mv) = book_move {
+                println!("bestmove {}", mv.to_uci());
+                return;
+            }
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut searcher = Searcher::new(stop.clone(), self.params.clone()).with_options(self.options.clone());
+        let position = self.position.clone();
+        let (stopped, released) = channel::<()>();
+
+        let handle = thread::spawn(move || {
+            let time = TimeManager::new(&time, position.active_colour);
+            let result = searcher.search(&position, &limits, time, print_info);
+
+            // the best move of an infinite search is only sent once it's been stopped
+            if limits.infinite {
+                let _ = released.recv();
+            }
+
+            match result.best_move {
+                Some(mv) => println!("bestmove {}", mv.to_uci()),
+                None => println!("bestmove 0000"),
+            }
+            let _ = io::stdout().flush();
+        });
+
+        self.search = Some((handle, stop, stopped));
+    }
+
+    /// Stops the running search, waiting for it to report its best move
+    fn stop(&mut self) {
+        if let Some((handle, stop, stopped)) = self.search.take() {
+            stop.store(true, Ordering::Relaxed);
+            drop(stopped);
+            let _ = handle.join();
+        }
+    }
+}
+
+fn print_info(info: &SearchInfo) {
+    println!(
+        "info depth {} multipv {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+        info.depth, info.multipv, info.score, info.nodes, info.nps, info.hashfull, info.time, info.pv.join(" "),
+    );
+    let _ = io::stdout().flush();
+}
+
+/// `startpos | fen <fen> [moves <move>...]`
+fn parse_position(tokens: &[&str]) -> Result<Position, String> {
+    let moves_at = tokens.iter().position(|token| *token == "moves").unwrap_or(tokens.len());
+
+    let mut position = match tokens.first() {
+        Some(&"startpos") => try_from_fen(START_FEN)?,
+        Some(&"fen") => try_from_fen(&tokens[1..moves_at].join(" "))?,
+        _ => return Err(String::from("position needs startpos or fen")),
+    };
+
+    for uci in tokens.iter().skip(moves_at + 1) {
+        let mv = parse_uci_move(&position, uci)?;
+        make_move(&mut position, &mv);
+    }
+
+    Ok(position)
+}
+
+/// The limits and clock of `go`, with nothing but the clock it searches until stopped
+fn parse_go(position: &Position, tokens: &[&str]) -> Result<(SearchLimits, TimeControl), String> {
+    let mut limits = SearchLimits::default();
+    let mut time = TimeControl::default();
+
+    let mut tokens = tokens.iter().peekable();
+    while let Some(token) = tokens.next() {
+        let mut value = || -> Result<u64, String> {
+            tokens.next()
+                .and_then(|value| value.parse().ok())
+                .ok_or(format!("{} needs a number", token))
+        };
+
+        match *token {
+            "depth" => limits.depth = Some(value()?.clamp(1, MAX_DEPTH as u64) as u8),
+            "nodes" => limits.nodes = Some(value()?),
+            "mate" => limits.mate = Some(value()?.clamp(1, MAX_DEPTH as u64) as u8),
+            "movetime" => time.movetime = Some(value()?),
+            "infinite" => limits.infinite = true,
+            "wtime" => time.wtime = Some(value()?),
+            "btime" => time.btime = Some(value()?),
+            "winc" => time.winc = Some(value()?),
+            "binc" => time.binc = Some(value()?),
+            "movestogo" => time.movestogo = Some(value()?),
+            "searchmoves" => {
+                while let Some(uci) = tokens.next_if(|token| !GO_KEYWORDS.contains(token)) {
+                    limits.searchmoves.push(parse_uci_move(position, uci)?);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    Ok((limits, time))
+}
+
+fn main() {
+    let mut uci = Uci::new();
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        match uci.handle(&line) {
+            Ok(true) => {},
+            Ok(false) => break,
+            Err(e) => println!("info string {}", e),
+        }
+        let _ = io::stdout().flush();
+    }
+
+    uci.stop();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::process::Command;
+
+    use super::{parse_go, parse_position, Uci, START_FEN};
+    use chess_engine::book::BookSelection;
+    use chess_engine::fen::from_fen;
+    use chess_engine::fen::to_fen;
+
+    #[test]
+    fn position_plays_the_moves() {
+        let position = parse_position(&["startpos", "moves", "e2e4", "e7e5"]).unwrap();
+        assert_eq!(to_fen(position), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR/");
+
+        let position = parse_position(&["fen", "4k3/8/8/8/8/8/8/R3K3", "w", "Q", "-", "0", "1", "moves", "e1c1"]).unwrap();
+        assert_eq!(to_fen(position), "4k3/8/8/8/8/8/8/2KR4/");
+
+        assert!(parse_position(&["startpos", "moves", "e2e5"]).is_err());
+    }
+
+    #[test]
+    fn position_keeps_stdout_for_the_protocol() {
+        let fen = ["fen", "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR", "b", "KQkq", "e3", "0", "1", "moves", "e7e5"];
+
+        // the test harness captures stdout, so the position is parsed again by a child whose stdout is piped back
+        if std::env::var_os("UCI_STDOUT_CHILD").is_some() {
+            let mut stdout = io::stdout();
+            write!(stdout, "<position>").unwrap();
+            parse_position(&fen).unwrap();
+            write!(stdout, "</position>").unwrap();
+            stdout.flush().unwrap();
+            return;
+        }
+
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(["tests::position_keeps_stdout_for_the_protocol", "--exact", "--nocapture", "--test-threads=1"])
+            .env("UCI_STDOUT_CHILD", "1")
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout).unwrap().contains("<position></position>"));
+    }
+
+    #[test]
+    fn go_reads_the_limits_and_clock() {
+        let position = from_fen(START_FEN);
+        let go = ["wtime", "1000", "btime", "2000", "depth", "6", "searchmoves", "e2e4", "d2d4", "nodes", "500", "mate", "3"];
+        let (limits, time) = parse_go(&position, &go).unwrap();
+
+        assert_eq!((limits.depth, limits.nodes, limits.mate, limits.infinite), (Some(6), Some(500), Some(3), false));
+        assert_eq!(limits.searchmoves.iter().map(|mv| mv.to_uci()).collect::<Vec<_>>(), ["e2e4", "d2d4"]);
+        assert_eq!((time.wtime, time.btime, time.movetime), (Some(1000), Some(2000), None));
+
+        let (limits, time) = parse_go(&position, &["movetime", "300", "infinite"]).unwrap();
+        assert_eq!((limits.infinite, time.movetime), (true, Some(300)));
+
+        assert!(parse_go(&position, &["movetime"]).is_err());
+        assert!(parse_go(&position, &["searchmoves", "e2e5"]).is_err());
+    }
+
+    #[test]
+    fn options_are_set_by_name() {
+        let mut uci = Uci::new();
+
+        uci.handle("setoption name MultiPV value 3").unwrap();
+        uci.handle("setoption name Threads value 2").unwrap();
+
+        assert_eq!((uci.options.multi_pv, uci.options.threads), (3, 2));
+        assert!(uci.handle("setoption name MultiPV value 0").is_err());
+        assert!(uci.handle("setoption name Hash value 16").is_err());
+    }
+
+    #[test]
+    fn book_options_are_set_by_name() {
+        let mut uci = Uci::new();
+
+        uci.handle("setoption name BookDepth value 8").unwrap();
+        uci.handle("setoption name BestBookMove value true").unwrap();
+        uci.handle("setoption name OwnBook value false").unwrap();
+
+        assert_eq!((uci.book.options.depth, uci.book.options.selection, uci.own_book), (8, BookSelection::Best, false));
+        assert!(uci.handle("setoption name BookFile value /nowhere/book.bin").is_err());
+        assert!(uci.handle("setoption name BookFile value <empty>").is_ok());
+        assert!(uci.handle("setoption name OwnBook value maybe").is_err());
+    }
+}
@@ -79,6 +79,11 @@ impl BitBoard {
         *self
     }
 
+    /// Flips the board vertically, rank 1 swaps with rank 8, as each rank is one byte
+    pub fn flip_vertical(self) -> BitBoard {
+        BitBoard(self.0.swap_bytes())
+    }
+
     pub fn print(self) {
         println!("Board:\t{:#066b}", (self).0)
     }
@@ -194,4 +199,12 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn flip_vertical_swaps_ranks() {
+        let a8_h1 = BitBoard(1 | 1 << 63);
+
+        assert_eq!(a8_h1.flip_vertical(), BitBoard(1 << 7 | 1 << 56));
+        assert_eq!(a8_h1.flip_vertical().flip_vertical(), a8_h1);
+    }
 }
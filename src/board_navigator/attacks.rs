@@ -0,0 +1,184 @@
+use crate::pieces::{Pieces, Side};
+
+/**
+ * Bitboard attack generation, where bit 0 is a8 and bit 63 is h1
+ *
+ * Directions are (x, y) in white's orientation, like `Coord::to`, so north moves towards bit 0
+ */
+pub const FILE_A: u64 = 0x0101_0101_0101_0101;
+pub const FILE_H: u64 = FILE_A << 7;
+
+pub const fn file_mask(file: usize) -> u64 {
+    FILE_A << file
+}
+
+pub const fn adjacent_files_mask(file: usize) -> u64 {
+    let west = if file > 0 { file_mask(file - 1) } else { 0 };
+    let east = if file < 7 { file_mask(file + 1) } else { 0 };
+    west | east
+}
+
+pub const fn rank_mask_of(square: usize) -> u64 {
+    0xFF << (square / 8 * 8)
+}
+
+/// 0 for the side's back rank up to 7 for the promotion rank
+pub fn relative_rank(side: Side, square: usize) -> usize {
+    match side {
+        Side::White => 7 - square / 8,
+        Side::Black => square / 8,
+    }
+}
+
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [(2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2), (1, -2), (2, -1)];
+const KING_OFFSETS: [(i32, i32); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+
+const DIAGONAL_DIRECTIONS: [(i32, i32); 4] = [(-1, 1), (1, 1), (1, -1), (-1, -1)];
+const STRAIGHT_DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+/// Returns the square moved to by (x, y) from the square, if it's on the board
+const fn offset_square(square: usize, x: i32, y: i32) -> Option<usize> {
+    let file = (square % 8) as i32 + x;
+    let row = (square / 8) as i32 - y;
+    if file < 0 || file > 7 || row < 0 || row > 7 {
+        None
+    } else {
+        Some((row * 8 + file) as usize)
+    }
+}
+
+const fn generate_leaper_attacks(offsets: [(i32, i32); 8]) -> [u64; 64] {
+    let mut attacks = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < 8 {
+            if let Some(to) = offset_square(square, offsets[i].0, offsets[i].1) {
+                attacks[square] |= 1 << to;
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    attacks
+}
+
+const KNIGHT_ATTACKS: [u64; 64] = generate_leaper_attacks(KNIGHT_OFFSETS);
+const KING_ATTACKS: [u64; 64] = generate_leaper_attacks(KING_OFFSETS);
+
+pub fn knight_attacks(square: usize) -> u64 {
+    KNIGHT_ATTACKS[square]
+}
+
+pub fn king_attacks(square: usize) -> u64 {
+    KING_ATTACKS[square]
+}
+
+/// The squares attacked by all the pawns of the side
+pub fn pawn_attacks(side: Side, pawns: u64) -> u64 {
+    match side {
+        Side::White => ((pawns & !FILE_A) >> 9) | ((pawns & !FILE_H) >> 7),
+        Side::Black => ((pawns & !FILE_A) << 7) | ((pawns & !FILE_H) << 9),
+    }
+}
+
+pub fn bishop_attacks(square: usize, occupancy: u64) -> u64 {
+    sliding_attacks(square, occupancy, &DIAGONAL_DIRECTIONS)
+}
+
+pub fn rook_attacks(square: usize, occupancy: u64) -> u64 {
+    sliding_attacks(square, occupancy, &STRAIGHT_DIRECTIONS)
+}
+
+pub fn queen_attacks(square: usize, occupancy: u64) -> u64 {
+    bishop_attacks(square, occupancy) | rook_attacks(square, occupancy)
+}
+
+/// Attacks of any piece but a pawn, whose attacks depend on its side
+pub fn piece_attacks(piece: usize, square: usize, occupancy: u64) -> u64 {
+    match piece {
+        Pieces::KNIGHT => knight_attacks(square),
+        Pieces::BISHOP => bishop_attacks(square, occupancy),
+        Pieces::ROOK => rook_attacks(square, occupancy),
+        Pieces::QUEEN => queen_attacks(square, occupancy),
+        Pieces::KING => king_attacks(square),
+        _ => 0,
+    }
+}
+
+/// Squares along each direction up to and including the first occupied one
+fn sliding_attacks(square: usize, occupancy: u64, directions: &[(i32, i32)]) -> u64 {
+    let mut attacks = 0;
+
+    for (x, y) in directions {
+        let mut current = square;
+        while let Some(next) = offset_square(current, *x, *y) {
+            attacks |= 1 << next;
+            if occupancy >> next & 1 == 1 {
+                break;
+            }
+            current = next;
+        }
+    }
+
+    attacks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bishop_attacks, king_attacks, knight_attacks, pawn_attacks, piece_attacks, queen_attacks, rook_attacks};
+    use crate::board_navigator::Coord;
+    use crate::pieces::{Pieces, Side};
+
+    fn square(file: char, rank: u8) -> usize {
+        Coord(file, rank).to_index() as usize
+    }
+
+    fn squares(coords: &[(char, u8)]) -> u64 {
+        coords.iter().fold(0, |bb, (file, rank)| bb | 1 << square(*file, *rank))
+    }
+
+    #[test]
+    fn knight_attacks_from_corner() {
+        assert_eq!(knight_attacks(square('a', 1)), squares(&[('b', 3), ('c', 2)]));
+    }
+
+    #[test]
+    fn king_attacks_from_edge() {
+        assert_eq!(king_attacks(square('e', 1)), squares(&[('d', 1), ('f', 1), ('d', 2), ('e', 2), ('f', 2)]));
+    }
+
+    #[test]
+    fn pawn_attacks_do_not_wrap() {
+        assert_eq!(pawn_attacks(Side::White, squares(&[('a', 2), ('h', 4)])), squares(&[('b', 3), ('g', 5)]));
+        assert_eq!(pawn_attacks(Side::Black, squares(&[('a', 7)])), squares(&[('b', 6)]));
+    }
+
+    #[test]
+    fn sliders_stop_at_first_blocker() {
+        let occupancy = squares(&[('d', 6), ('f', 4)]);
+
+        assert_eq!(
+            rook_attacks(square('d', 4), occupancy),
+            squares(&[
+                ('d', 5), ('d', 6),
+                ('e', 4), ('f', 4),
+                ('d', 3), ('d', 2), ('d', 1),
+                ('c', 4), ('b', 4), ('a', 4),
+            ])
+        );
+        assert_eq!(
+            bishop_attacks(square('a', 1), occupancy),
+            squares(&[('b', 2), ('c', 3), ('d', 4), ('e', 5), ('f', 6), ('g', 7), ('h', 8)])
+        );
+    }
+
+    #[test]
+    fn piece_attacks_match_the_piece() {
+        let square = square('d', 4);
+
+        assert_eq!(piece_attacks(Pieces::KNIGHT, square, 0), knight_attacks(square));
+        assert_eq!(piece_attacks(Pieces::QUEEN, square, 0), queen_attacks(square, 0));
+        assert_eq!(piece_attacks(Pieces::PAWN, square, 0), 0);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::position::Position;
-use crate::pieces::Side;
+use crate::pieces::{Pieces, Side};
 use crate::utils::{coord_from_index, is_rank};
 
 use super::Coord;
@@ -8,7 +8,7 @@ use super::is_attacked;
  /**
   * A second, separate way to do proper move notation { piece: Piece, toCoord, capture: boolean, castling: Q | K, promotionPiece: Piece, disambiguation: file | rank | { file, rank } }
   */
-  #[derive(Debug, PartialEq)]
+  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Piece {
     P,
     N,
@@ -31,7 +31,7 @@ impl std::fmt::Display for Piece {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CastlingSide {
     WK,
     WQ,
@@ -42,14 +42,28 @@ pub enum CastlingSide {
 /**
  * An efficient way to denote a move that is not "proper move notation"
  */
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Move {
-    from: Coord,
-    to: Coord,
-    capture: bool,
-    en_passant: bool, // just a bool, as the Position holds the en_passant_target
-    castling: Option<CastlingSide>, // optional
-    promotion: Option<Piece>, // optional
+    pub from: Coord,
+    pub to: Coord,
+    pub capture: bool,
+    pub en_passant: bool, // just a bool, as the Position holds the en_passant_target
+    pub castling: Option<CastlingSide>, // optional
+    pub promotion: Option<Piece>, // optional
+}
+
+impl Move {
+    /// Long algebraic notation as used by UCI, eg: e2e4, e7e8q
+    pub fn to_uci(&self) -> String {
+        let promo = match &self.promotion {
+            Some(Piece::N) => "n",
+            Some(Piece::B) => "b",
+            Some(Piece::R) => "r",
+            Some(Piece::Q) => "q",
+            _ => "",
+        };
+        format!("{}{}{}", self.from, self.to, promo)
+    }
 }
 
 impl std::fmt::Display for Move {
@@ -117,18 +131,91 @@ pub fn get_piece_movements(position: &Position) -> Vec<Move> {
 
 }
 
-pub fn make_move(position: &mut Position, mv: &Move) -> /*Position*/ () {
+/// Returns the moves for the active colour that do not leave its own king in check
+pub fn get_legal_moves(position: &Position) -> Vec<Move> {
+    let side = position.active_colour;
+    let in_check = position.checkers().0 != 0;
+    let may_expose_king = position.pinned().0 | position.get_coloured_pieces(side, Pieces::KING).0;
+
+    get_piece_movements(position)
+        .into_iter()
+        .filter(|mv| {
+            // out of check, only the king, a pinned piece or en passant can leave the king attacked
+            if !in_check && !mv.en_passant && (1u64 << mv.from.to_index()) & may_expose_king == 0 {
+                return true;
+            }
+
+            let mut next = position.clone();
+            make_move(&mut next, mv);
+            !is_side_in_check(&next, side)
+        })
+        .collect()
+}
+
+/// Finds the legal move written in long algebraic notation, eg: e2e4, e7e8q
+pub fn parse_uci_move(position: &Position, uci: &str) -> Result<Move, String> {
+    get_legal_moves(position)
+        .into_iter()
+        .find(|mv| mv.to_uci() == uci)
+        .ok_or_else(|| format!("Illegal move '{}'", uci))
+}
+
+/// Returns true if the king of the active colour is attacked
+pub fn is_in_check(position: &Position) -> bool {
+    position.checkers().0 != 0
+}
+
+pub fn make_move(position: &mut Position, mv: &Move) {
+    let from_index = mv.from.to_index();
+    let to_index = mv.to.to_index();
+    let is_pawn_move = position.is_pawn(from_index);
+
     if mv.capture {
         if mv.en_passant {
             position.remove_pawn_by_en_passant();
         } else {
-            position.remove_piece(mv.to.to_index());
+            position.remove_piece(to_index);
         }
     }
 
-    let result = position.make_move(&mv.from, &mv.to, &mv.castling);
+    // the target only lasts for one move, make_move sets it again after a double pawn push
+    position.en_passant_target = None;
+
+    if position.make_move(&mv.from, &mv.to, &mv.castling).is_err() {
+        return;
+    }
+
+    if let Some(promotion) = mv.promotion {
+        position.place_piece(to_index, position.active_colour, promotion);
+    }
+
+    revoke_castling_rights(position, &mv.from);
+    revoke_castling_rights(position, &mv.to);
+
+    if is_pawn_move || mv.capture {
+        position.half_move_clock = 0;
+    } else {
+        position.half_move_clock += 1;
+    }
+
+    if position.active_colour == Side::Black {
+        position.full_moves += 1;
+    }
+
+    position.active_colour = position.active_colour.opponent();
+}
 
-    position.print();
+/// Moving a king or rook, or capturing a rook on its starting square, loses the right to castle with it
+fn revoke_castling_rights(position: &mut Position, coord: &Coord) {
+    match (coord.0, coord.1) {
+        ('e', 1) => { position.castling.K = false; position.castling.Q = false; },
+        ('h', 1) => position.castling.K = false,
+        ('a', 1) => position.castling.Q = false,
+        ('e', 8) => { position.castling.k = false; position.castling.q = false; },
+        ('h', 8) => position.castling.k = false,
+        ('a', 8) => position.castling.q = false,
+        _ => (),
+    }
 }
 
 fn get_pawn_movements(position: &Position, direction: i32, index: i32) -> Vec<Move> {
@@ -183,6 +270,11 @@ fn get_knight_movements(position: &Position, index: i32) -> Vec<Move> {
                     (position.is_black(coord.to_index()) && position.is_white(to.to_index()))
                 );
 
+                // blocked by our own piece
+                if position.has_piece(to.to_index()) && !capture {
+                    continue;
+                }
+
                 movements.push(
                     Move {
                         from: coord.clone(),
@@ -253,9 +345,9 @@ fn get_king_movements(position: &Position, direction: i32, index: i32) -> Vec<Mo
     }
 
     // castling
-    if !is_in_check(position, direction) {
+    if !is_side_in_check(position, position.active_colour) {
         if direction > 0 && is_rank(index, 1) {
-            if position.castling.K && are_coords_clear(position, vec![Coord('f',1), Coord('g',1)], direction) {
+            if position.castling.K && are_coords_clear(position, vec![Coord('f',1), Coord('g',1)], position.active_colour.opponent()) {
                 movements.push(Move {
                     from: coord_from_index(index),
                     to: Coord('g', 1),
@@ -265,7 +357,8 @@ fn get_king_movements(position: &Position, direction: i32, index: i32) -> Vec<Mo
                     promotion: None,
                 });
             }
-            if position.castling.Q && are_coords_clear(position, vec![Coord('d',1), Coord('c',1), Coord('b',1)], direction) {
+            // the rook passes over the b file but the king doesn't, so it only needs to be empty
+            if position.castling.Q && !position.has_piece(Coord('b',1).to_index()) && are_coords_clear(position, vec![Coord('d',1), Coord('c',1)], position.active_colour.opponent()) {
                 movements.push(Move {
                     from: coord_from_index(index),
                     to: Coord('c', 1),
@@ -276,7 +369,7 @@ fn get_king_movements(position: &Position, direction: i32, index: i32) -> Vec<Mo
                 });
             }
         } else if direction < 0 && is_rank(index, 8) {
-            if position.castling.k && are_coords_clear(position, vec![Coord('f',8), Coord('g',8)], direction) {
+            if position.castling.k && are_coords_clear(position, vec![Coord('f',8), Coord('g',8)], position.active_colour.opponent()) {
                 movements.push(Move {
                     from: coord_from_index(index),
                     to: Coord('g', 8),
@@ -286,7 +379,8 @@ fn get_king_movements(position: &Position, direction: i32, index: i32) -> Vec<Mo
                     promotion: None,
                 });
             }
-            if position.castling.q && are_coords_clear(position, vec![Coord('d',8), Coord('c',8), Coord('b',8)], direction) {
+            // the rook passes over the b file but the king doesn't, so it only needs to be empty
+            if position.castling.q && !position.has_piece(Coord('b',8).to_index()) && are_coords_clear(position, vec![Coord('d',8), Coord('c',8)], position.active_colour.opponent()) {
                 movements.push(Move {
                     from: coord_from_index(index),
                     to: Coord('c', 8),
@@ -302,13 +396,13 @@ fn get_king_movements(position: &Position, direction: i32, index: i32) -> Vec<Mo
     movements
 }
 
-fn are_coords_clear(position: &Position, coords: Vec<Coord>, direction :i32) -> bool {
+fn are_coords_clear(position: &Position, coords: Vec<Coord>, by: Side) -> bool {
     for coord in coords {
         if position.has_piece(coord.to_index()) {
             return false;
         }
 
-        if is_attacked(position, &coord, direction) {
+        if is_attacked(position, &coord, by) {
             return false;
         }
     }
@@ -316,15 +410,10 @@ fn are_coords_clear(position: &Position, coords: Vec<Coord>, direction :i32) ->
     return true
 }
 
-fn is_in_check(position: &Position, direction: i32) -> bool {
-    let king_bb = if direction > 0 { position.get_white_kings() } else { position.get_black_kings() };
-    let king_indicies = king_bb.get_indicies();
-    let king_index_opt = king_indicies.get(0);
-    if let Some(king_index) = king_index_opt {
-        let king_coord = coord_from_index(*king_index as i32);
-        return is_attacked(position, &king_coord, direction);
-    } else {
-        false // no king on the board??
+fn is_side_in_check(position: &Position, side: Side) -> bool {
+    match position.get_coloured_pieces(side, Pieces::KING).get_indicies().first() {
+        Some(king) => is_attacked(position, &coord_from_index(*king as i32), side.opponent()),
+        None => false, // no king on the board??
     }
 }
 
@@ -504,7 +593,6 @@ fn get_pawn_promotion_movements(from: Coord, to: Coord, capture: bool) -> Vec<Mo
         Piece::B,
         Piece::R,
         Piece::Q,
-        Piece::K,
     ] {
         movements.push(
             Move {
@@ -520,3 +608,57 @@ fn get_pawn_promotion_movements(from: Coord, to: Coord, capture: bool) -> Vec<Mo
 
     movements
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{get_legal_moves, make_move};
+    use crate::fen::from_fen;
+    use crate::position::Position;
+
+    fn perft(position: &Position, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        get_legal_moves(position).iter().map(|mv| {
+            let mut next = position.clone();
+            make_move(&mut next, mv);
+            perft(&next, depth - 1)
+        }).sum()
+    }
+
+    #[test]
+    fn parse_uci_move() {
+        let position = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+
+        assert_eq!(super::parse_uci_move(&position, "e2e4").map(|mv| mv.to_uci()), Ok(String::from("e2e4")));
+        assert_eq!(super::parse_uci_move(&position, "e2e5"), Err(String::from("Illegal move 'e2e5'")));
+    }
+
+    #[test]
+    fn perft_starting_position() {
+        let position = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+        assert_eq!(perft(&position, 1), 20);
+        assert_eq!(perft(&position, 2), 400);
+        assert_eq!(perft(&position, 3), 8902);
+    }
+
+    #[test]
+    fn perft_kiwipete() {
+        let position = from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
+        assert_eq!(perft(&position, 1), 48);
+        assert_eq!(perft(&position, 2), 2039);
+    }
+
+    #[test]
+    fn perft_promotions_and_en_passant() {
+        let position = from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -");
+        assert_eq!(perft(&position, 1), 14);
+        assert_eq!(perft(&position, 2), 191);
+        assert_eq!(perft(&position, 3), 2812);
+
+        let position = from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq -");
+        assert_eq!(perft(&position, 1), 6);
+        assert_eq!(perft(&position, 2), 264);
+    }
+}
@@ -1,151 +1,38 @@
-use crate::{pieces::Pieces, position::Position, utils::coord_from_index};
+use crate::{bitboard::BitBoard, pieces::{Pieces, Side}, position::Position};
 use super::Coord;
+use super::attacks::{bishop_attacks, king_attacks, knight_attacks, pawn_attacks, rook_attacks};
 
-#[derive(Clone)]
-struct AttackVector<'a> {
-    directions: &'a[&'a(i32, i32)],
-    piece_types: &'a[usize], // vec of Pieces
-    ranged: bool,
-}
-
-/**
- * directions to check in a straight line for an attacking bishop, queen - nw, ne, se, sw
- */
-const DIAGONAL_DIRECTIONS: &[&(i32, i32)] = &[
-    &( -1,  1 ),
-    &(  1,  1 ),
-    &(  1, -1 ),
-    &( -1, -1 ),
-];
-
-/**
- * directions to check in a straight line for an attacking rook, queen - n, e, s, w
- */
-const STRAIGHT_DIRECTIONS: &[&(i32, i32)] = &[
-    &(  0,  1 ),
-    &(  1,  0 ),
-    &(  0, -1 ),
-    &( -1,  0 ),
-];
-
-const ALL_DIRECTIONS: &[&(i32, i32)] = &[
-    &( -1,  1 ),
-    &(  1,  1 ),
-    &(  1, -1 ),
-    &( -1, -1 ),
-    &(  0,  1 ),
-    &(  1,  0 ),
-    &(  0, -1 ),
-    &( -1,  0 ),
-];
-
-/**
- * Ranged piece types and the directions in which they can move
- */
-const ATTACK_VECTORS: [AttackVector; 3] = [
-    AttackVector {
-        directions: DIAGONAL_DIRECTIONS,
-        piece_types: &[Pieces::BISHOP, Pieces::QUEEN],
-        ranged: true
-    },
-    AttackVector {
-        directions: STRAIGHT_DIRECTIONS,
-        piece_types: &[Pieces::ROOK, Pieces::QUEEN],
-        ranged: true
-    },
-    AttackVector {
-        directions: ALL_DIRECTIONS,
-        piece_types: &[Pieces::KING],
-        ranged: false
-    }
-];
-
-pub fn is_attacked(position: &Position, coord: &Coord, direction: i32) -> bool {
-
-    let pawn_attack_vector = AttackVector {
-        directions: &[&(-1, direction), &(1, direction)],
-        piece_types: &[Pieces::PAWN],
-        ranged: false,
-    };
-
-    let mut attack_vectors: Vec<AttackVector> = ATTACK_VECTORS.to_vec();
-    attack_vectors.push(pawn_attack_vector);
-
-    let mut next_coord = coord.clone();
-    // scout lines
-    for attack_vector in attack_vectors {
-        for dir in attack_vector.directions {
-
-            if attack_vector.ranged {
-                while let Some(c) = next_coord.to(dir.0, dir.1) {
-                    next_coord = c;
-                    let next_coord_index = next_coord.to_index();
-    
-                    if position.has_piece(next_coord_index) {
-                        if has_attacking_piece(position, next_coord_index, direction, attack_vector.piece_types) {
-                            return true;
-                        }
-    
-                        // reset coord
-                        next_coord = coord.clone();
-                        break;
-                    }
-                }
-            } else {
-                if let Some(c) = next_coord.to(dir.0, dir.1) {
-                    let next_coord_index = c.to_index();
-
-                    if has_attacking_piece(position, next_coord_index, direction, attack_vector.piece_types) {
-                        println!("piece on {} is attacking {}", coord_from_index(next_coord_index), coord);
-                        return true;
-                    }
-                }
-            }
-        }
-    }
-
-    return false;
-}
+/// Pieces of the side attacking the square, as if only the squares in `occupancy` were occupied.
+/// Pieces outside `occupancy` are left out, so sliders behind a removed piece show up as x-rays.
+pub fn attackers_to(position: &Position, square: usize, side: Side, occupancy: BitBoard) -> BitBoard {
+    let own = position.get_side_pieces(side).0 & occupancy.0;
+    let pieces = |piece: usize| position.pieces[piece].0 & own;
 
-fn has_attacking_piece(position: &Position, index: i32, direction: i32, piece_types: &[usize]) -> bool {
-    if position.has_piece(index) {
-        if is_opposing_piece(position, index, direction) && is_piece_of_type(position, index, piece_types) {
-            return true;
-        }
-    }
+    let diagonal = pieces(Pieces::BISHOP) | pieces(Pieces::QUEEN);
+    let straight = pieces(Pieces::ROOK) | pieces(Pieces::QUEEN);
 
-    false
-}
+    // a pawn of the side attacks the square if a pawn of the opponent on the square would attack it
+    let attackers = pawn_attacks(side.opponent(), 1 << square) & pieces(Pieces::PAWN) |
+        knight_attacks(square) & pieces(Pieces::KNIGHT) |
+        king_attacks(square) & pieces(Pieces::KING) |
+        bishop_attacks(square, occupancy.0) & diagonal |
+        rook_attacks(square, occupancy.0) & straight;
 
-fn is_opposing_piece(position: &Position, index: i32, direction: i32) -> bool {
-    if direction > 0 { position.is_black(index) } else { position.is_white(index) }
+    BitBoard(attackers)
 }
 
-// checks if position has a piece of one of the given piece types at the given index
-fn is_piece_of_type(position: &Position, index: i32, types: &[usize]) -> bool {
-    for piece_type in types {
-        let is_of_type = match piece_type {
-            &Pieces::PAWN   => position.is_pawn(index),
-            &Pieces::BISHOP => position.is_bishop(index),
-            &Pieces::KNIGHT => position.is_knight(index),
-            &Pieces::ROOK => position.is_rook(index),
-            &Pieces::QUEEN => position.is_queen(index),
-            &Pieces::KING => position.is_king(index),
-            _ => false,
-        };
-
-        if is_of_type {
-            return true;
-        }
-    }
-    return false;
+/// Returns true if any piece of the side attacks the square
+pub fn is_attacked(position: &Position, coord: &Coord, by: Side) -> bool {
+    attackers_to(position, coord.to_index() as usize, by, position.get_occupied()).count() > 0
 }
 
 #[cfg(test)]
 mod tests {
     use super::Coord;
-    use super::is_attacked;
+    use super::{attackers_to, is_attacked};
     use crate::fen::{from_fen, fen_to_asci_board};
+    use crate::bitboard::BitBoard;
+    use crate::pieces::Side;
 
     #[test]
     fn is_attacked_by_pawn() {
@@ -154,7 +41,7 @@ mod tests {
         let coord = Coord('f',1);
     
         let expected = true;
-        let actual = is_attacked(&position, &coord, 1);
+        let actual = is_attacked(&position, &coord, Side::Black);
     
         assert_eq!(expected, actual, "expected {} but got {}", expected, actual);
     }
@@ -166,7 +53,7 @@ mod tests {
         let coord = Coord('f',1);
     
         let expected = false;
-        let actual = is_attacked(&position, &coord, 1);
+        let actual = is_attacked(&position, &coord, Side::Black);
     
         assert_attacked(expected, actual, &coord, fen);
     }
@@ -178,7 +65,7 @@ mod tests {
         let coord = Coord('f',1);
     
         let expected = true;
-        let actual = is_attacked(&position, &coord, 1);
+        let actual = is_attacked(&position, &coord, Side::Black);
     
         assert_attacked(expected, actual, &coord, fen);
     }
@@ -190,7 +77,7 @@ mod tests {
         let coord = Coord('f',1);
     
         let expected = true;
-        let actual = is_attacked(&position, &coord, 1);
+        let actual = is_attacked(&position, &coord, Side::Black);
     
         assert_attacked(expected, actual, &coord, fen);
     }
@@ -202,11 +89,40 @@ mod tests {
         let coord = Coord('f',1);
     
         let expected = false;
-        let actual = is_attacked(&position, &coord, 1);
+        let actual = is_attacked(&position, &coord, Side::Black);
     
         assert_attacked(expected, actual, &coord, fen);
     }
     
+    #[test]
+    fn is_attacked_by_knight() {
+        let fen = "rnbqkb1r/pppppppp/8/8/8/3n4/PPPPPPPP/RNBQKBNR w KQkq -";
+        let position = from_fen(fen);
+        let coord = Coord('e',1);
+    
+        let expected = true;
+        let actual = is_attacked(&position, &coord, Side::Black);
+    
+        assert_attacked(expected, actual, &coord, fen);
+    }
+    
+    #[test]
+    fn attackers_to_includes_x_rays_when_removed() {
+        // white rooks doubled on the d file, black knight and pawn defending d5
+        let position = from_fen("4k3/8/2p2n2/3p4/8/8/3R4/3RK3 w - -");
+        let square = Coord('d', 5).to_index() as usize;
+        let front_rook = Coord('d', 2).to_index() as usize;
+
+        let white = attackers_to(&position, square, Side::White, position.get_occupied());
+        let black = attackers_to(&position, square, Side::Black, position.get_occupied());
+        assert_eq!(white.get_indicies(), vec![front_rook as u32]);
+        assert_eq!(black.count(), 2);
+
+        let without_front_rook = position.get_occupied().0 & !(1 << front_rook);
+        let x_ray = attackers_to(&position, square, Side::White, BitBoard(without_front_rook));
+        assert_eq!(x_ray.get_indicies(), vec![Coord('d', 1).to_index() as u32]);
+    }
+
     fn assert_attacked(expected: bool, actual: bool, coord: &Coord, fen: &str) {
         assert_eq!(
             expected,
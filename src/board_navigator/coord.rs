@@ -1,6 +1,6 @@
 
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct Coord (pub char, pub u8);
 
 impl std::fmt::Display for Coord {
@@ -3,14 +3,25 @@
 // ======================
 
 // modules
+mod attacks;
 mod board_navigator;
 mod board_scout;
 mod coord;
 
 // exports from module
 pub use board_navigator::get_piece_movements;
+pub use board_navigator::get_legal_moves;
+pub use board_navigator::is_in_check;
+pub use board_navigator::make_move;
+pub use board_navigator::parse_uci_move;
+pub use board_navigator::Move;
 pub use board_navigator::Piece;
 pub use board_navigator::CastlingSide;
+pub use board_scout::attackers_to;
 pub use coord::Coord;
+pub use attacks::{
+    adjacent_files_mask, bishop_attacks, file_mask, king_attacks, pawn_attacks, piece_attacks, rank_mask_of,
+    relative_rank, rook_attacks,
+};
 
 use board_scout::is_attacked;
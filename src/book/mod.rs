@@ -0,0 +1,14 @@
+// ===========
+// Book Module
+// ===========
+
+// modules
+mod keys;
+mod polyglot;
+
+// exports from module
+pub use keys::polyglot_key;
+pub use polyglot::BookMove;
+pub use polyglot::BookOptions;
+pub use polyglot::BookSelection;
+pub use polyglot::OpeningBook;
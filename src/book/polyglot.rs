@@ -0,0 +1,286 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rocket::fairing::AdHoc;
+use rocket::serde::Deserialize;
+
+use crate::board_navigator::{parse_uci_move, Move};
+use crate::position::Position;
+
+use super::keys::polyglot_key;
+
+/// Bytes in each entry of a `.bin` book: key, move, weight and learning data, all big-endian
+const ENTRY_SIZE: usize = 16;
+
+/// How the move is chosen when a position has several in the book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum BookSelection {
+    /// at random, in proportion to the weights
+    #[default]
+    Weighted,
+    /// always the one with the highest weight
+    Best,
+}
+
+/**
+ * Where the book is and how it's played from, set in the Rocket config under `book`
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct BookOptions {
+    /// a Polyglot `.bin` file, no book is used without one
+    pub file: Option<PathBuf>,
+    /// the book is only played from up to this full move
+    pub depth: usize,
+    pub selection: BookSelection,
+}
+
+impl Default for BookOptions {
+    fn default() -> BookOptions {
+        BookOptions {
+            file: None,
+            depth: 20,
+            selection: BookSelection::Weighted,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BookEntry {
+    key: u64,
+    /// to square, from square and promotion piece, 6, 6 and 3 bits from the lowest
+    raw_move: u16,
+    weight: u16,
+}
+
+/// A move of the book and how often it should be played, relative to the others
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookMove {
+    pub mv: Move,
+    pub weight: u16,
+}
+
+/**
+ * An opening book in the Polyglot format, its entries sorted by key
+ */
+#[derive(Debug, Clone, Default)]
+pub struct OpeningBook {
+    entries: Vec<BookEntry>,
+    pub options: BookOptions,
+}
+
+impl OpeningBook {
+    /// Reads the book at `options.file`, or makes an empty one without a file
+    pub fn open(options: BookOptions) -> Result<OpeningBook, String> {
+        let Some(path) = &options.file else {
+            return Ok(OpeningBook { entries: vec![], options });
+        };
+
+        let bytes = fs::read(path).map_err(|e| format!("Can't read the opening book {}: {}", path.display(), e))?;
+        let path = path.display().to_string();
+        OpeningBook::from_bytes(&bytes, options).map_err(|e| format!("Invalid opening book {}: {}", path, e))
+    }
+
+    pub fn from_bytes(bytes: &[u8], options: BookOptions) -> Result<OpeningBook, String> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(format!("its size isn't a multiple of {} bytes", ENTRY_SIZE));
+        }
+
+        let mut entries: Vec<BookEntry> = bytes.chunks_exact(ENTRY_SIZE)
+            .map(|entry| BookEntry {
+                key: u64::from_be_bytes(entry[0..8].try_into().unwrap()),
+                raw_move: u16::from_be_bytes(entry[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(entry[10..12].try_into().unwrap()),
+            })
+            .collect();
+
+        // books are sorted already, this is for any that aren't
+        entries.sort_by_key(|entry| entry.key);
+
+        Ok(OpeningBook { entries, options })
+    }
+
+    /// The legal moves the book has for the position, highest weight first
+    pub fn moves(&self, position: &Position) -> Vec<BookMove> {
+        let key = polyglot_key(position);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+
+        let mut moves: Vec<BookMove> = self.entries[start..].iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| {
+                let mv = parse_uci_move(position, &decode_move(position, entry.raw_move)).ok()?;
+                Some(BookMove { mv, weight: entry.weight })
+            })
+            .collect();
+
+        moves.sort_by_key(|book_move| std::cmp::Reverse(book_move.weight));
+        moves
+    }
+
+    /// The move to play from the book, if the position is in it and within the book depth
+    pub fn probe(&self, position: &Position) -> Option<Move> {
+        if position.full_moves > self.options.depth {
+            return None;
+        }
+
+        choose(self.moves(position), self.options.selection, random())
+    }
+}
+
+impl OpeningBook {
+    /// Manages the book from `book` in the Rocket config, an empty one when it isn't set
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Opening book", |rocket| async {
+            let options = match rocket.figment().find_value("book") {
+                Ok(_) => rocket.figment().extract_inner::<BookOptions>("book").map_err(|e| format!("Invalid book options: {}", e)),
+                Err(_) => Ok(BookOptions::default()),
+            };
+
+            match options.and_then(OpeningBook::open) {
+                Ok(book) => Ok(rocket.manage(book)),
+                Err(error) => {
+                    rocket::error!("{}", error);
+                    Err(rocket)
+                },
+            }
+        })
+    }
+}
+
+/// `roll` picks the move when weighted, any number will do
+fn choose(moves: Vec<BookMove>, selection: BookSelection, roll: u64) -> Option<Move> {
+    let total: u64 = moves.iter().map(|book_move| book_move.weight as u64).sum();
+
+    // moves that are all weighted 0 are still in the book, they're just equally likely
+    if selection == BookSelection::Best || total == 0 {
+        return moves.into_iter().next().map(|book_move| book_move.mv);
+    }
+
+    let mut roll = roll % total;
+    moves.into_iter()
+        .find(|book_move| {
+            let weight = book_move.weight as u64;
+            if roll < weight {
+                return true;
+            }
+            roll -= weight;
+            false
+        })
+        .map(|book_move| book_move.mv)
+}
+
+/// Long algebraic notation of a book move, castling is stored as the king taking its own rook
+fn decode_move(position: &Position, raw_move: u16) -> String {
+    let square = |index: u16| format!("{}{}", (b'a' + (index % 8) as u8) as char, index / 8 + 1);
+    let to = raw_move & 0x3f;
+    let from = (raw_move >> 6) & 0x3f;
+
+    let promotion = match (raw_move >> 12) & 0x7 {
+        1 => "n",
+        2 => "b",
+        3 => "r",
+        4 => "q",
+        _ => "",
+    };
+
+    // book squares count from a1, the board's from a8
+    let is_king = position.is_king((from ^ 56) as i32);
+    let to = match (square(from).as_str(), square(to).as_str()) {
+        ("e1", "h1") | ("e8", "h8") if is_king => to - 1,
+        ("e1", "a1") | ("e8", "a8") if is_king => to + 2,
+        _ => to,
+    };
+
+    format!("{}{}{}", square(from), square(to), promotion)
+}
+
+/// Seeded from the clock, good enough to vary the opening from game to game
+fn random() -> u64 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+
+    // splitmix64, to spread the bits of the clock
+    let mut z = nanos.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{choose, BookOptions, BookSelection, OpeningBook};
+    use crate::book::polyglot_key;
+    use crate::fen::from_fen;
+    use crate::position::Position;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    /// A move as Polyglot stores it, squares counted from a1
+    fn raw_move(from: u16, to: u16) -> u16 {
+        to | from << 6
+    }
+
+    fn entry(position: &Position, raw_move: u16, weight: u16) -> Vec<u8> {
+        let mut bytes = polyglot_key(position).to_be_bytes().to_vec();
+        bytes.extend(raw_move.to_be_bytes());
+        bytes.extend(weight.to_be_bytes());
+        bytes.extend([0; 4]);
+        bytes
+    }
+
+    fn book(entries: &[Vec<u8>], options: BookOptions) -> OpeningBook {
+        OpeningBook::from_bytes(&entries.concat(), options).unwrap()
+    }
+
+    #[test]
+    fn moves_are_read_best_first() {
+        let position = from_fen(START);
+        // e2e4, d2d4, and e2e5 which isn't legal
+        let book = book(&[entry(&position, raw_move(12, 28), 10), entry(&position, raw_move(11, 27), 30), entry(&position, raw_move(12, 36), 50)], BookOptions::default());
+
+        let moves: Vec<(String, u16)> = book.moves(&position).iter().map(|book_move| (book_move.mv.to_uci(), book_move.weight)).collect();
+        assert_eq!(moves, [(String::from("d2d4"), 30), (String::from("e2e4"), 10)]);
+        assert!(book.moves(&from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1")).is_empty());
+    }
+
+    #[test]
+    fn selection_by_weight() {
+        let position = from_fen(START);
+        let book = book(&[entry(&position, raw_move(12, 28), 10), entry(&position, raw_move(11, 27), 30)], BookOptions::default());
+
+        assert_eq!(choose(book.moves(&position), BookSelection::Best, 35).unwrap().to_uci(), "d2d4");
+        assert_eq!(choose(book.moves(&position), BookSelection::Weighted, 29).unwrap().to_uci(), "d2d4");
+        assert_eq!(choose(book.moves(&position), BookSelection::Weighted, 35).unwrap().to_uci(), "e2e4");
+    }
+
+    #[test]
+    fn castling_is_stored_as_taking_the_rook() {
+        let position = from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let book = book(&[entry(&position, raw_move(4, 7), 1), entry(&position, raw_move(4, 0), 1)], BookOptions::default());
+
+        let mut moves: Vec<String> = book.moves(&position).iter().map(|book_move| book_move.mv.to_uci()).collect();
+        moves.sort();
+        assert_eq!(moves, ["e1c1", "e1g1"]);
+    }
+
+    #[test]
+    fn book_is_only_played_up_to_its_depth() {
+        let early = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 3");
+        let late = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 4");
+        let options = BookOptions { depth: 3, ..BookOptions::default() };
+        let book = book(&[entry(&early, raw_move(12, 28), 1)], options);
+
+        assert_eq!(book.probe(&early).unwrap().to_uci(), "e2e4");
+        assert!(book.probe(&late).is_none());
+    }
+
+    #[test]
+    fn truncated_book_is_an_error() {
+        let position = from_fen(START);
+        let mut bytes = entry(&position, raw_move(12, 28), 1);
+        bytes.pop();
+
+        assert!(OpeningBook::from_bytes(&bytes, BookOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::http::{Header, Method, Status};
+use rocket::serde::Deserialize;
+use rocket::{Build, Request, Response, Rocket};
+
+use std::io::Cursor;
+
+/**
+ * CORS policy, read from the `cors` table of Rocket.toml or `ROCKET_CORS` in the environment
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct CorsConfig {
+    /// origins allowed to call the API, "*" allows any origin
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    /// seconds a browser may cache the result of a preflight request
+    pub max_age: Option<u32>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> CorsConfig {
+        CorsConfig {
+            allowed_origins: vec![String::from("*")],
+            allowed_methods: ["GET", "POST", "PATCH", "DELETE", "OPTIONS"].map(String::from).to_vec(),
+            allowed_headers: vec![String::from("Content-Type")],
+            allow_credentials: false,
+            max_age: Some(86400),
+        }
+    }
+}
+
+impl CorsConfig {
+    fn is_any_origin_allowed(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+
+    fn is_origin_allowed(&self, origin: &str) -> bool {
+        self.is_any_origin_allowed() || self.allowed_origins.iter().any(|allowed| allowed == origin)
+    }
+
+    fn allow_origin_header(&self, origin: &str) -> String {
+        if self.is_any_origin_allowed() {
+            String::from("*")
+        } else {
+            String::from(origin)
+        }
+    }
+}
+
+/// Whether a mounted route has the request's path, whatever its method
+fn has_route(request: &Request<'_>) -> bool {
+    let path: Vec<_> = request.uri().path().segments().collect();
+
+    request.rocket().routes().any(|route| {
+        let segments: Vec<_> = route.uri.path().split('/').filter(|segment| !segment.is_empty()).collect();
+
+        // a trailing `<path..>` segment matches the rest of the path, even none of it
+        let lengths_match = match segments.last() {
+            Some(last) if last.ends_with("..>") => path.len() + 1 >= segments.len(),
+            _ => path.len() == segments.len(),
+        };
+
+        lengths_match && segments.iter().zip(&path).all(|(segment, part)| segment.starts_with('<') || segment == part)
+    })
+}
+
+/**
+ * Adds CORS headers to responses for allowed origins and answers their preflight requests for every route
+ */
+pub struct Cors;
+
+#[rocket::async_trait]
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info {
+            name: "Add CORS headers to responses",
+            kind: Kind::Ignite | Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let config = match rocket.figment().find_value("cors") {
+            Ok(_) => rocket.figment().extract_inner::<CorsConfig>("cors"),
+            Err(_) => Ok(CorsConfig::default()),
+        };
+
+        match config {
+            // echoing any origin back with credentials would let every site make credentialed requests
+            Ok(config) if config.is_any_origin_allowed() && config.allow_credentials => {
+                rocket::error!("Invalid CORS configuration: allow_credentials needs a list of origins, not \"*\"");
+                Err(rocket)
+            },
+            Ok(config) => Ok(rocket.manage(config)),
+            Err(error) => {
+                rocket::error!("Invalid CORS configuration: {}", error);
+                Err(rocket)
+            },
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(config) = request.rocket().state::<CorsConfig>() else {
+            return;
+        };
+
+        let Some(origin) = request.headers().get_one("Origin") else {
+            return;
+        };
+
+        if !config.is_origin_allowed(origin) {
+            return;
+        }
+
+        let is_preflight = request.method() == Method::Options
+            && request.headers().contains("Access-Control-Request-Method")
+            && has_route(request);
+
+        if is_preflight {
+            // there are no OPTIONS routes, so answer here rather than with a 404
+            response.set_status(Status::NoContent);
+            response.set_sized_body(0, Cursor::new(""));
+        }
+
+        let allow_origin = config.allow_origin_header(origin);
+        if allow_origin != "*" {
+            response.adjoin_header(Header::new("Vary", "Origin"));
+        }
+        response.set_header(Header::new("Access-Control-Allow-Origin", allow_origin));
+
+        if config.allow_credentials {
+            response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        }
+
+        if is_preflight {
+            response.set_header(Header::new("Access-Control-Allow-Methods", config.allowed_methods.join(", ")));
+            response.set_header(Header::new("Access-Control-Allow-Headers", config.allowed_headers.join(", ")));
+
+            if let Some(max_age) = config.max_age {
+                response.set_header(Header::new("Access-Control-Max-Age", max_age.to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::error::ErrorKind;
+    use rocket::figment::Figment;
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+    use rocket::Config;
+
+    use crate::build_rocket;
+
+    fn client(cors: Option<Figment>) -> Client {
+        let figment = match cors {
+            Some(cors) => Config::figment().merge(cors),
+            None => Config::figment(),
+        };
+        Client::tracked(build_rocket(figment)).unwrap()
+    }
+
+    fn restricted() -> Figment {
+        Figment::new()
+            .merge(("cors.allowed_origins", vec!["https://board.example.com"]))
+            .merge(("cors.allow_credentials", true))
+    }
+
+    #[test]
+    fn default_allows_any_origin_without_credentials() {
+        let client = client(None);
+        let response = client.get("/").header(Header::new("Origin", "https://anywhere.example.com")).dispatch();
+
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Origin"), Some("*"));
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Credentials"), None);
+    }
+
+    #[test]
+    fn preflight_is_answered_for_any_route() {
+        let client = client(None);
+
+        for uri in ["/evaluate", "/analyse/3"] {
+            let response = client.options(uri)
+                .header(Header::new("Origin", "https://anywhere.example.com"))
+                .header(Header::new("Access-Control-Request-Method", "POST"))
+                .dispatch();
+
+            assert_eq!(response.status(), Status::NoContent);
+            assert_eq!(response.headers().get_one("Access-Control-Allow-Methods"), Some("GET, POST, PATCH, DELETE, OPTIONS"));
+            assert_eq!(response.headers().get_one("Access-Control-Allow-Headers"), Some("Content-Type"));
+            assert_eq!(response.headers().get_one("Access-Control-Max-Age"), Some("86400"));
+        }
+    }
+
+    #[test]
+    fn any_origin_with_credentials_fails_to_launch() {
+        let figment = Config::figment().merge(("cors.allow_credentials", true));
+
+        match Client::tracked(build_rocket(figment)) {
+            Err(error) => assert!(matches!(error.kind(), ErrorKind::FailedFairings(_))),
+            Ok(_) => panic!("launched allowing credentials from any origin"),
+        }
+    }
+
+    #[test]
+    fn configured_origin_is_echoed_with_credentials() {
+        let client = client(Some(restricted()));
+        let response = client.get("/").header(Header::new("Origin", "https://board.example.com")).dispatch();
+
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Origin"), Some("https://board.example.com"));
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Credentials"), Some("true"));
+        assert_eq!(response.headers().get_one("Vary"), Some("Origin"));
+    }
+
+    #[test]
+    fn other_origins_get_no_cors_headers() {
+        let client = client(Some(restricted()));
+        let response = client.get("/").header(Header::new("Origin", "https://evil.example.com")).dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Origin"), None);
+    }
+
+    #[test]
+    fn plain_options_request_is_not_a_preflight() {
+        let client = client(None);
+        let response = client.options("/evaluate").dispatch();
+
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn preflight_for_unknown_route_is_not_found() {
+        let client = client(None);
+        let response = client.options("/nowhere")
+            .header(Header::new("Origin", "https://anywhere.example.com"))
+            .header(Header::new("Access-Control-Request-Method", "POST"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn preflight_from_other_origin_is_not_answered() {
+        let client = client(Some(restricted()));
+        let response = client.options("/evaluate")
+            .header(Header::new("Origin", "https://evil.example.com"))
+            .header(Header::new("Access-Control-Request-Method", "POST"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Methods"), None);
+    }
+}
@@ -0,0 +1,97 @@
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::board_navigator::file_mask;
+use crate::pieces::{Pieces, Side};
+use crate::position::Position;
+
+use super::trace::SideScores;
+use super::TaperedScore;
+
+/// Centipawns in the middlegame and endgame
+struct PieceActivity;
+impl PieceActivity {
+    /// rook on a file without pawns
+    pub const ROOK_OPEN_FILE: TaperedScore = TaperedScore::new(25, 10);
+    /// rook on a file with enemy pawns only
+    pub const ROOK_HALF_OPEN_FILE: TaperedScore = TaperedScore::new(12, 6);
+    /// two or more bishops
+    pub const BISHOP_PAIR: TaperedScore = TaperedScore::new(30, 50);
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct ActivityParams {
+    pub rook_open_file: TaperedScore,
+    pub rook_half_open_file: TaperedScore,
+    pub bishop_pair: TaperedScore,
+}
+
+impl Default for ActivityParams {
+    fn default() -> ActivityParams {
+        ActivityParams {
+            rook_open_file: PieceActivity::ROOK_OPEN_FILE,
+            rook_half_open_file: PieceActivity::ROOK_HALF_OPEN_FILE,
+            bishop_pair: PieceActivity::BISHOP_PAIR,
+        }
+    }
+}
+
+/// Rooks on open files and the bishop pair of each side
+pub fn evaluate_activity(position: &Position, params: &ActivityParams) -> SideScores {
+    SideScores::new(calc_activity(position, Side::White, params), calc_activity(position, Side::Black, params))
+}
+
+fn calc_activity(position: &Position, side: Side, params: &ActivityParams) -> TaperedScore {
+    let own_pawns = position.get_coloured_pieces(side, Pieces::PAWN).0;
+    let enemy_pawns = position.get_coloured_pieces(side.opponent(), Pieces::PAWN).0;
+
+    let mut score = TaperedScore::default();
+
+    let mut rooks = position.get_coloured_pieces(side, Pieces::ROOK).0;
+    while rooks != 0 {
+        let file = file_mask(rooks.trailing_zeros() as usize % 8);
+        rooks &= rooks - 1;
+
+        if own_pawns & file == 0 {
+            score += if enemy_pawns & file == 0 {
+                params.rook_open_file
+            } else {
+                params.rook_half_open_file
+            };
+        }
+    }
+
+    if position.material_key.count(side, Pieces::BISHOP) >= 2 {
+        score += params.bishop_pair;
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calc_activity, ActivityParams, PieceActivity};
+    use crate::evaluator::TaperedScore;
+    use crate::fen::from_fen;
+    use crate::pieces::Side;
+
+    #[test]
+    fn rooks_on_open_and_half_open_files() {
+        // d-file is open, e-file only has a black pawn and the a-file is closed by a white pawn
+        let position = from_fen("6k1/4p3/8/8/8/8/P7/R2RR1K1 w - -");
+
+        assert_eq!(
+            calc_activity(&position, Side::White, &ActivityParams::default()),
+            PieceActivity::ROOK_OPEN_FILE + PieceActivity::ROOK_HALF_OPEN_FILE
+        );
+    }
+
+    #[test]
+    fn bishop_pair() {
+        let pair = from_fen("4k3/8/8/8/8/8/8/2B1KB2 w - -");
+        let single = from_fen("4k3/8/8/8/8/8/8/2B1KN2 w - -");
+
+        assert_eq!(calc_activity(&pair, Side::White, &ActivityParams::default()), PieceActivity::BISHOP_PAIR);
+        assert_eq!(calc_activity(&single, Side::White, &ActivityParams::default()), TaperedScore::default());
+    }
+}
@@ -0,0 +1,210 @@
+use rocket::serde::Serialize;
+
+use crate::material::MaterialKey;
+use crate::pieces::{Pieces, Side};
+use crate::position::Position;
+
+use super::kpk;
+use super::params::EvalParams;
+
+/// Centipawns on top of the material for an ending that's won with correct play, so that
+/// reaching one is preferred over keeping more material in an unclear position
+const KNOWN_WIN: i32 = 2000;
+
+/// Centipawns for the winning side per square the losing king is pushed from the centre
+const PUSH_TO_EDGE: i32 = 20;
+/// Centipawns for the winning side per square the losing king is from the wrong corner
+const PUSH_TO_CORNER: i32 = 20;
+/// Centipawns for the winning side per square its king is closer to the losing king
+const PUSH_CLOSE: i32 = 10;
+
+/**
+ * An elementary ending that the general evaluation gets wrong, told apart by the material on the board
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub enum Endgame {
+    /// king and pawn against king, looked up in the bitbase
+    #[serde(rename = "KPK")]
+    Kpk,
+    /// a queen or rook against a bare king, mated by driving the king to the edge
+    #[serde(rename = "KXK")]
+    Kxk,
+    /// bishop and knight against a bare king, mated in a corner of the bishop's colour
+    #[serde(rename = "KBNK")]
+    Kbnk,
+    /// bare kings, or a lone bishop or knight, which can't mate
+    #[serde(rename = "draw")]
+    InsufficientMaterial,
+}
+
+/// The specialised ending for the material, with the side that's trying to win
+pub fn find_endgame(material: MaterialKey) -> Option<(Endgame, Side)> {
+    for (strong, weak) in [(Side::White, Side::Black), (Side::Black, Side::White)] {
+        if !material.is_bare_king(weak) {
+            continue;
+        }
+
+        let count = |piece: usize| material.count(strong, piece);
+        let minors = count(Pieces::BISHOP) + count(Pieces::KNIGHT);
+        let heavies = count(Pieces::ROOK) + count(Pieces::QUEEN);
+
+        let endgame = match (count(Pieces::PAWN), minors, heavies) {
+            (0, 0, 0) | (0, 1, 0) => Endgame::InsufficientMaterial,
+            (1, 0, 0) => Endgame::Kpk,
+            (0, 2, 0) if count(Pieces::BISHOP) == 1 => Endgame::Kbnk,
+            (_, _, 1..) => Endgame::Kxk,
+            _ => continue,
+        };
+
+        return Some((endgame, strong));
+    }
+
+    None
+}
+
+/// Centipawns, positive when white is better
+pub fn evaluate_endgame(position: &Position, endgame: Endgame, strong: Side, params: &EvalParams) -> i32 {
+    let strong_king = king_square(position, strong);
+    let weak_king = king_square(position, strong.opponent());
+
+    let score = match endgame {
+        Endgame::InsufficientMaterial => 0,
+        Endgame::Kpk => evaluate_kpk(position, strong, strong_king, weak_king, params),
+        Endgame::Kxk => {
+            KNOWN_WIN + material(position, strong, params) +
+                PUSH_TO_EDGE * centre_distance(weak_king) +
+                PUSH_CLOSE * (7 - distance(strong_king, weak_king))
+        },
+        Endgame::Kbnk => {
+            // a1 and h8 for a dark squared bishop, which is on a8 to h1's colour in board indices
+            let bishop = position.get_coloured_pieces(strong, Pieces::BISHOP).0.trailing_zeros() as usize;
+            let corners = if is_dark(bishop) { [56, 7] } else { [0, 63] };
+            let corner_distance = corners.iter().map(|corner| manhattan_distance(weak_king, *corner)).min().unwrap();
+
+            KNOWN_WIN + material(position, strong, params) +
+                PUSH_TO_CORNER * (14 - corner_distance) +
+                PUSH_CLOSE * (7 - distance(strong_king, weak_king))
+        },
+    };
+
+    match strong {
+        Side::White => score,
+        Side::Black => -score,
+    }
+}
+
+fn evaluate_kpk(position: &Position, strong: Side, strong_king: usize, weak_king: usize, params: &EvalParams) -> i32 {
+    let pawn = position.get_coloured_pieces(strong, Pieces::PAWN).0.trailing_zeros() as usize;
+
+    // the bitbase has the strong side playing up the board with squares from a1, white's board
+    // indices run from a8 so they're flipped, black's already run up the board from its side
+    let to_bitbase = |square: usize| match strong {
+        Side::White => square ^ 56,
+        Side::Black => square,
+    };
+
+    if !kpk::probe(to_bitbase(strong_king), to_bitbase(pawn), to_bitbase(weak_king), position.active_colour == strong) {
+        return 0;
+    }
+
+    // the further up the pawn is, the closer it is to promoting
+    KNOWN_WIN + params.material.values.pawn.eg + (to_bitbase(pawn) / 8) as i32 * PUSH_CLOSE
+}
+
+/// Endgame values of the side's pieces
+fn material(position: &Position, side: Side, params: &EvalParams) -> i32 {
+    let values = &params.material.values;
+    [
+        (Pieces::PAWN, values.pawn.eg),
+        (Pieces::BISHOP, values.bishop.eg),
+        (Pieces::KNIGHT, values.knight.eg),
+        (Pieces::ROOK, values.rook.eg),
+        (Pieces::QUEEN, values.queen.eg),
+    ].iter().map(|(piece, value)| position.material_key.count(side, *piece) as i32 * value).sum()
+}
+
+fn king_square(position: &Position, side: Side) -> usize {
+    position.get_coloured_pieces(side, Pieces::KING).0.trailing_zeros() as usize
+}
+
+/// Squares a king needs to walk from one to the other
+fn distance(a: usize, b: usize) -> i32 {
+    (a % 8).abs_diff(b % 8).max((a / 8).abs_diff(b / 8)) as i32
+}
+
+fn manhattan_distance(a: usize, b: usize) -> i32 {
+    ((a % 8).abs_diff(b % 8) + (a / 8).abs_diff(b / 8)) as i32
+}
+
+/// From 0 on the four centre squares to 6 in the corners
+fn centre_distance(square: usize) -> i32 {
+    let from_centre = |line: usize| if line < 4 { 3 - line } else { line - 4 };
+    (from_centre(square % 8) + from_centre(square / 8)) as i32
+}
+
+/// Board indices run from a8, which is a light square
+fn is_dark(square: usize) -> bool {
+    (square % 8 + square / 8) % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate_endgame, find_endgame, Endgame, KNOWN_WIN};
+    use crate::evaluator::EvalParams;
+    use crate::fen::from_fen;
+    use crate::pieces::Side;
+
+    fn evaluate(fen: &str) -> i32 {
+        let position = from_fen(fen);
+        let (endgame, strong) = find_endgame(position.material_key).unwrap();
+        evaluate_endgame(&position, endgame, strong, &EvalParams::default())
+    }
+
+    #[test]
+    fn endgames_are_told_apart_by_material() {
+        let endgame = |fen: &str| find_endgame(from_fen(fen).material_key);
+
+        assert_eq!(endgame("8/8/4k3/8/8/8/4P3/4K3 w - -"), Some((Endgame::Kpk, Side::White)));
+        assert_eq!(endgame("8/4p3/4k3/8/8/8/8/4K3 w - -"), Some((Endgame::Kpk, Side::Black)));
+        assert_eq!(endgame("8/8/4k3/8/8/8/8/R3K3 w - -"), Some((Endgame::Kxk, Side::White)));
+        assert_eq!(endgame("8/8/4k3/8/8/8/3QP3/R3K3 w - -"), Some((Endgame::Kxk, Side::White)));
+        assert_eq!(endgame("8/8/4k3/8/8/8/8/2B1KN2 w - -"), Some((Endgame::Kbnk, Side::White)));
+        assert_eq!(endgame("8/8/4k3/8/8/8/8/2B1K3 w - -"), Some((Endgame::InsufficientMaterial, Side::White)));
+        assert_eq!(endgame("8/8/4k3/8/8/8/8/4K3 w - -"), Some((Endgame::InsufficientMaterial, Side::White)));
+
+        // both sides have material, or it's left to the general evaluation
+        assert_eq!(endgame("8/8/4k3/4p3/8/8/4P3/4K3 w - -"), None);
+        assert_eq!(endgame("8/8/4k3/8/8/8/3PP3/4K3 w - -"), None);
+        assert_eq!(endgame("8/8/4k3/8/8/8/8/2B1KB2 w - -"), None);
+    }
+
+    #[test]
+    fn kpk_knows_the_opposition() {
+        assert!(evaluate("8/4k3/8/4K3/4P3/8/8/8 b - -") > KNOWN_WIN);
+        assert_eq!(evaluate("8/4k3/8/4K3/4P3/8/8/8 w - -"), 0);
+        assert_eq!(evaluate("8/8/8/4p3/4k3/8/4K3/8 b - -"), 0);
+        assert!(evaluate("8/8/8/4p3/4k3/8/4K3/8 w - -") < -KNOWN_WIN);
+    }
+
+    #[test]
+    fn lone_king_is_driven_to_the_edge() {
+        let centre = evaluate("8/8/8/3k4/8/8/8/R3K3 w - -");
+        let edge = evaluate("3k4/8/8/8/8/8/8/R3K3 w - -");
+        let edge_near_king = evaluate("8/8/8/8/8/8/8/R2k1K2 b - -");
+
+        assert!(centre > KNOWN_WIN);
+        assert!(edge > centre);
+        assert!(edge_near_king > edge);
+    }
+
+    #[test]
+    fn bishop_and_knight_mate_in_the_bishops_corner() {
+        // a dark squared bishop on c1 mates on a1 or h8
+        let right_corner = evaluate("7k/8/8/8/8/8/8/2B1KN2 w - -");
+        let wrong_corner = evaluate("k7/8/8/8/8/8/8/2B1KN2 w - -");
+
+        assert!(right_corner > wrong_corner);
+        assert!(wrong_corner > KNOWN_WIN);
+    }
+}
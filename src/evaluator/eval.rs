@@ -0,0 +1,555 @@
+use std::ops::{Add, AddAssign, Mul, Sub};
+
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::pieces::{Pieces, Side};
+use crate::position::Position;
+use crate::score::Score;
+
+use super::activity::evaluate_activity;
+use super::endgames::evaluate_endgame;
+use super::king_safety::evaluate_king_safety;
+use super::material_table::{probe_material, MAX_PHASE};
+use super::mobility::evaluate_mobility;
+use super::params::{square_table, EvalParams};
+use super::pawns::evaluate_pawns;
+use super::trace::{EvalTrace, SideScores};
+
+/**
+ * A score for the middlegame and one for the endgame, blended together by the game phase
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TaperedScore {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl TaperedScore {
+    pub const fn new(mg: i32, eg: i32) -> TaperedScore {
+        TaperedScore { mg, eg }
+    }
+
+    /// Interpolates between the middlegame and endgame scores, see `game_phase`
+    pub fn taper(self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for TaperedScore {
+    type Output = TaperedScore;
+
+    fn add(self, other: TaperedScore) -> TaperedScore {
+        TaperedScore::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl Sub for TaperedScore {
+    type Output = TaperedScore;
+
+    fn sub(self, other: TaperedScore) -> TaperedScore {
+        TaperedScore::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Mul<i32> for TaperedScore {
+    type Output = TaperedScore;
+
+    fn mul(self, times: i32) -> TaperedScore {
+        TaperedScore::new(self.mg * times, self.eg * times)
+    }
+}
+
+impl AddAssign for TaperedScore {
+    fn add_assign(&mut self, other: TaperedScore) {
+        *self = *self + other;
+    }
+}
+
+/// Centipawns in the middlegame and endgame
+struct PieceValue;
+impl PieceValue {
+    pub const PAWN: TaperedScore = TaperedScore::new(82, 94);
+    pub const BISHOP: TaperedScore = TaperedScore::new(365, 297);
+    pub const KNIGHT: TaperedScore = TaperedScore::new(337, 281);
+    pub const ROOK: TaperedScore = TaperedScore::new(477, 512);
+    pub const QUEEN: TaperedScore = TaperedScore::new(1025, 936);
+    // KING has infinite value, only its square counts
+    pub const KING: TaperedScore = TaperedScore::new(0, 0);
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct PieceValues {
+    pub pawn: TaperedScore,
+    pub bishop: TaperedScore,
+    pub knight: TaperedScore,
+    pub rook: TaperedScore,
+    pub queen: TaperedScore,
+}
+
+impl Default for PieceValues {
+    fn default() -> PieceValues {
+        PieceValues {
+            pawn: PieceValue::PAWN,
+            bishop: PieceValue::BISHOP,
+            knight: PieceValue::KNIGHT,
+            rook: PieceValue::ROOK,
+            queen: PieceValue::QUEEN,
+        }
+    }
+}
+
+/**
+ * Centipawns added to the piece value based on where it is located on the board,
+ * in the middlegame (MG) and in the endgame (EG)
+ *
+ * Laid out from white's point of view, black's tables are mirrored from these when they're built
+ * 
+ * 8
+ * 7
+ * 6
+ * 5
+ * 4
+ * 3
+ * 2
+ * 1
+ * a     b     c     d     e     f     g     h
+ * 
+ */
+const PAWN_MG: [i32; 64] = [
+       0,    0,    0,    0,    0,    0,    0,    0,
+      98,  134,   61,   95,   68,  126,   34,  -11,
+      -6,    7,   26,   31,   65,   56,   25,  -20,
+     -14,   13,    6,   21,   23,   12,   17,  -23,
+     -27,   -2,   -5,   12,   17,    6,   10,  -25,
+     -26,   -4,   -4,  -10,    3,    3,   33,  -12,
+     -35,   -1,  -20,  -23,  -15,   24,   38,  -22,
+       0,    0,    0,    0,    0,    0,    0,    0,
+];
+
+const PAWN_EG: [i32; 64] = [
+       0,    0,    0,    0,    0,    0,    0,    0,
+     178,  173,  158,  134,  147,  132,  165,  187,
+      94,  100,   85,   67,   56,   53,   82,   84,
+      32,   24,   13,    5,   -2,    4,   17,   17,
+      13,    9,   -3,   -7,   -7,   -8,    3,   -1,
+       4,    7,   -6,    1,    0,   -5,   -1,   -8,
+      13,    8,    8,   10,   13,    0,    2,   -7,
+       0,    0,    0,    0,    0,    0,    0,    0,
+];
+
+const KNIGHT_MG: [i32; 64] = [
+    -167,  -89,  -34,  -49,   61,  -97,  -15, -107,
+     -73,  -41,   72,   36,   23,   62,    7,  -17,
+     -47,   60,   37,   65,   84,  129,   73,   44,
+      -9,   17,   19,   53,   37,   69,   18,   22,
+     -13,    4,   16,   13,   28,   19,   21,   -8,
+     -23,   -9,   12,   10,   19,   17,   25,  -16,
+     -29,  -53,  -12,   -3,   -1,   18,  -14,  -19,
+    -105,  -21,  -58,  -33,  -17,  -28,  -19,  -23,
+];
+
+const KNIGHT_EG: [i32; 64] = [
+     -58,  -38,  -13,  -28,  -31,  -27,  -63,  -99,
+     -25,   -8,  -25,   -2,   -9,  -25,  -24,  -52,
+     -24,  -20,   10,    9,   -1,   -9,  -19,  -41,
+     -17,    3,   22,   22,   22,   11,    8,  -18,
+     -18,   -6,   16,   25,   16,   17,    4,  -18,
+     -23,   -3,   -1,   15,   10,   -3,  -20,  -22,
+     -42,  -20,  -10,   -5,   -2,  -20,  -23,  -44,
+     -29,  -51,  -23,  -15,  -22,  -18,  -50,  -64,
+];
+
+const BISHOP_MG: [i32; 64] = [
+     -29,    4,  -82,  -37,  -25,  -42,    7,   -8,
+     -26,   16,  -18,  -13,   30,   59,   18,  -47,
+     -16,   37,   43,   40,   35,   50,   37,   -2,
+      -4,    5,   19,   50,   37,   37,    7,   -2,
+      -6,   13,   13,   26,   34,   12,   10,    4,
+       0,   15,   15,   15,   14,   27,   18,   10,
+       4,   15,   16,    0,    7,   21,   33,    1,
+     -33,   -3,  -14,  -21,  -13,  -12,  -39,  -21,
+];
+
+const BISHOP_EG: [i32; 64] = [
+     -14,  -21,  -11,   -8,   -7,   -9,  -17,  -24,
+      -8,   -4,    7,  -12,   -3,  -13,   -4,  -14,
+       2,   -8,    0,   -1,   -2,    6,    0,    4,
+      -3,    9,   12,    9,   14,   10,    3,    2,
+      -6,    3,   13,   19,    7,   10,   -3,   -9,
+     -12,   -3,    8,   10,   13,    3,   -7,  -15,
+     -14,  -18,   -7,   -1,    4,   -9,  -15,  -27,
+     -23,   -9,  -23,   -5,   -9,  -16,   -5,  -17,
+];
+
+const ROOK_MG: [i32; 64] = [
+      32,   42,   32,   51,   63,    9,   31,   43,
+      27,   32,   58,   62,   80,   67,   26,   44,
+      -5,   19,   26,   36,   17,   45,   61,   16,
+     -24,  -11,    7,   26,   24,   35,   -8,  -20,
+     -36,  -26,  -12,   -1,    9,   -7,    6,  -23,
+     -45,  -25,  -16,  -17,    3,    0,   -5,  -33,
+     -44,  -16,  -20,   -9,   -1,   11,   -6,  -71,
+     -19,  -13,    1,   17,   16,    7,  -37,  -26,
+];
+
+const ROOK_EG: [i32; 64] = [
+      13,   10,   18,   15,   12,   12,    8,    5,
+      11,   13,   13,   11,   -3,    3,    8,    3,
+       7,    7,    7,    5,    4,   -3,   -5,   -3,
+       4,    3,   13,    1,    2,    1,   -1,    2,
+       3,    5,    8,    4,   -5,   -6,   -8,  -11,
+      -4,    0,   -5,   -1,   -7,  -12,   -8,  -16,
+      -6,   -6,    0,    2,   -9,   -9,  -11,   -3,
+      -9,    2,    3,   -1,   -5,  -13,    4,  -20,
+];
+
+const QUEEN_MG: [i32; 64] = [
+     -28,    0,   29,   12,   59,   44,   43,   45,
+     -24,  -39,   -5,    1,  -16,   57,   28,   54,
+     -13,  -17,    7,    8,   29,   56,   47,   57,
+     -27,  -27,  -16,  -16,   -1,   17,   -2,    1,
+      -9,  -26,   -9,  -10,   -2,   -4,    3,   -3,
+     -14,    2,  -11,   -2,   -5,    2,   14,    5,
+     -35,   -8,   11,    2,    8,   15,   -3,    1,
+      -1,  -18,   -9,   10,  -15,  -25,  -31,  -50,
+];
+
+const QUEEN_EG: [i32; 64] = [
+      -9,   22,   22,   27,   27,   19,   10,   20,
+     -17,   20,   32,   41,   58,   25,   30,    0,
+     -20,    6,    9,   49,   47,   35,   19,    9,
+       3,   22,   24,   45,   57,   40,   57,   36,
+     -18,   28,   19,   47,   31,   34,   39,   23,
+     -16,  -27,   15,    6,    9,   17,   10,    5,
+     -22,  -23,  -30,  -16,  -16,  -23,  -36,  -32,
+     -33,  -28,  -22,  -43,   -5,  -32,  -20,  -41,
+];
+
+const KING_MG: [i32; 64] = [
+     -65,   23,   16,  -15,  -56,  -34,    2,   13,
+      29,   -1,  -20,   -7,   -8,   -4,  -38,  -29,
+      -9,   24,    2,  -16,  -20,    6,   22,  -22,
+     -17,  -20,  -12,  -27,  -30,  -25,  -14,  -36,
+     -49,   -1,  -27,  -39,  -46,  -44,  -33,  -51,
+     -14,  -14,  -22,  -46,  -44,  -30,  -15,  -27,
+       1,    7,   -8,  -64,  -43,  -16,    9,    8,
+     -15,   36,   12,  -54,    8,  -28,   24,   14,
+];
+
+const KING_EG: [i32; 64] = [
+     -74,  -35,  -18,  -18,  -11,   15,    4,  -17,
+     -12,   17,   14,   17,   17,   38,   23,   11,
+      10,   17,   23,   15,   20,   45,   44,   13,
+      -8,   22,   24,   27,   26,   33,   26,    3,
+     -18,   -4,   21,   24,   27,   23,    9,  -11,
+     -19,   -3,   11,   21,   23,   16,    7,   -9,
+     -27,  -11,    4,   13,   14,    4,   -5,  -17,
+     -53,  -34,  -21,  -11,  -28,  -14,  -24,  -43,
+];
+
+/// The middlegame and endgame tables of a piece for each side, only white's are (de)serialized
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", from = "WhiteTables", into = "WhiteTables")]
+pub struct PieceSquareTable {
+    /// white's then black's
+    mg: [[i32; 64]; 2],
+    eg: [[i32; 64]; 2],
+}
+
+impl PieceSquareTable {
+    /// Builds black's tables by mirroring white's
+    pub const fn new(mg: [i32; 64], eg: [i32; 64]) -> PieceSquareTable {
+        PieceSquareTable { mg: [mg, mirrored(mg)], eg: [eg, mirrored(eg)] }
+    }
+
+    /// Bonus for the side's piece on the square
+    fn bonus(&self, side: Side, square: usize) -> TaperedScore {
+        let side = match side {
+            Side::White => 0,
+            Side::Black => 1,
+        };
+
+        TaperedScore::new(self.mg[side][square], self.eg[side][square])
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct WhiteTables {
+    #[serde(with = "square_table")]
+    mg: [i32; 64],
+    #[serde(with = "square_table")]
+    eg: [i32; 64],
+}
+
+impl From<WhiteTables> for PieceSquareTable {
+    fn from(tables: WhiteTables) -> PieceSquareTable {
+        PieceSquareTable::new(tables.mg, tables.eg)
+    }
+}
+
+impl From<PieceSquareTable> for WhiteTables {
+    fn from(table: PieceSquareTable) -> WhiteTables {
+        WhiteTables { mg: table.mg[0], eg: table.eg[0] }
+    }
+}
+
+/// The table seen from the other side of the board, a8 <-> a1
+const fn mirrored(table: [i32; 64]) -> [i32; 64] {
+    let mut mirrored = [0; 64];
+
+    let mut square = 0;
+    while square < 64 {
+        mirrored[square] = table[square ^ 56];
+        square += 1;
+    }
+
+    mirrored
+}
+
+struct DefaultPieceSquareTables;
+impl DefaultPieceSquareTables {
+    const PAWN: PieceSquareTable = PieceSquareTable::new(PAWN_MG, PAWN_EG);
+    const BISHOP: PieceSquareTable = PieceSquareTable::new(BISHOP_MG, BISHOP_EG);
+    const KNIGHT: PieceSquareTable = PieceSquareTable::new(KNIGHT_MG, KNIGHT_EG);
+    const ROOK: PieceSquareTable = PieceSquareTable::new(ROOK_MG, ROOK_EG);
+    const QUEEN: PieceSquareTable = PieceSquareTable::new(QUEEN_MG, QUEEN_EG);
+    const KING: PieceSquareTable = PieceSquareTable::new(KING_MG, KING_EG);
+}
+
+/// One table per piece from white's point of view, shared by both sides
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct PieceSquareTables {
+    pub pawn: PieceSquareTable,
+    pub bishop: PieceSquareTable,
+    pub knight: PieceSquareTable,
+    pub rook: PieceSquareTable,
+    pub queen: PieceSquareTable,
+    pub king: PieceSquareTable,
+}
+
+impl Default for PieceSquareTables {
+    fn default() -> PieceSquareTables {
+        PieceSquareTables {
+            pawn: DefaultPieceSquareTables::PAWN,
+            bishop: DefaultPieceSquareTables::BISHOP,
+            knight: DefaultPieceSquareTables::KNIGHT,
+            rook: DefaultPieceSquareTables::ROOK,
+            queen: DefaultPieceSquareTables::QUEEN,
+            king: DefaultPieceSquareTables::KING,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct MaterialParams {
+    pub values: PieceValues,
+    pub pst: PieceSquareTables,
+}
+
+/// Static evaluation from the point of view of the side to move
+pub fn evaluate_position(position: &Position, params: &EvalParams) -> Score {
+    Score::relative(evaluate_with_trace(position, params).total, position.active_colour)
+}
+
+/// The evaluation along with the contribution of each term for each side
+pub fn evaluate_with_trace(position: &Position, params: &EvalParams) -> EvalTrace {
+    let material = probe_material(position.material_key, &params.imbalance);
+    let phase = material.phase;
+    let (white_material, white_pst) = calc_material(position, Side::White, &params.material);
+    let (black_material, black_pst) = calc_material(position, Side::Black, &params.material);
+
+    let mut trace = EvalTrace {
+        phase,
+        material: SideScores::new(white_material, black_material),
+        pst: SideScores::new(white_pst, black_pst),
+        imbalance: material.imbalance,
+        pawns: evaluate_pawns(position, &params.pawns),
+        mobility: evaluate_mobility(position, &params.mobility),
+        activity: evaluate_activity(position, &params.activity),
+        king_safety: evaluate_king_safety(position, &params.king_safety),
+        endgame: None,
+        total: 0,
+    };
+
+    let score = trace.terms().iter().fold(TaperedScore::default(), |score, term| score + term.difference());
+    trace.total = score.taper(phase);
+
+    // the terms are still traced, but an elementary ending knows better than them
+    if let Some((endgame, strong)) = material.endgame {
+        trace.endgame = Some(endgame);
+        trace.total = evaluate_endgame(position, endgame, strong, params);
+    }
+
+    // weights can come from a request, and however large they are a static evaluation isn't a mate
+    trace.total = trace.total.clamp(-Score::MATE_BOUND.0 + 1, Score::MATE_BOUND.0 - 1);
+
+    trace
+}
+
+/// Piece values and piece-square table bonuses of the side
+fn calc_material(position: &Position, side: Side, params: &MaterialParams) -> (TaperedScore, TaperedScore) {
+    let values = &params.values;
+    let tables = &params.pst;
+    let pieces = [
+        (Pieces::PAWN, values.pawn, &tables.pawn),
+        (Pieces::BISHOP, values.bishop, &tables.bishop),
+        (Pieces::KNIGHT, values.knight, &tables.knight),
+        (Pieces::ROOK, values.rook, &tables.rook),
+        (Pieces::QUEEN, values.queen, &tables.queen),
+        (Pieces::KING, PieceValue::KING, &tables.king),
+    ];
+
+    let mut material = TaperedScore::default();
+    let mut pst = TaperedScore::default();
+
+    for (piece, piece_value, table) in pieces {
+        material += piece_value * position.material_key.count(side, piece) as i32;
+
+        for i in position.get_coloured_pieces(side, piece).get_indicies() {
+            pst += table.bonus(side, i as usize);
+        }
+    }
+
+    (material, pst)
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::serde::json::{from_str, to_string};
+
+    use super::{evaluate_position, evaluate_with_trace, EvalParams, PieceSquareTable, PieceValue, TaperedScore, MAX_PHASE};
+    use crate::evaluator::material_table::game_phase;
+    use crate::evaluator::Endgame;
+    use crate::fen::from_fen;
+    use crate::pieces::Side;
+    use crate::score::Score;
+
+    #[test]
+    fn starting_position_is_level() {
+        let position = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+
+        assert_eq!(game_phase(position.material_key), MAX_PHASE);
+        assert_eq!(evaluate_position(&position, &EvalParams::default()), Score::DRAW);
+    }
+
+    #[test]
+    fn evaluation_is_from_side_to_move() {
+        let white = from_fen("4k3/8/8/8/8/8/3Q4/4K3 w - -");
+        let black = from_fen("4k3/8/8/8/8/8/3Q4/4K3 b - -");
+
+        assert!(evaluate_position(&white, &EvalParams::default()) > Score::DRAW);
+        assert_eq!(evaluate_position(&black, &EvalParams::default()), -evaluate_position(&white, &EvalParams::default()));
+    }
+
+    #[test]
+    fn taper_blends_by_phase() {
+        let score = TaperedScore::new(100, -20);
+
+        assert_eq!(score.taper(MAX_PHASE), 100);
+        assert_eq!(score.taper(0), -20);
+        assert_eq!(score.taper(MAX_PHASE / 2), 40);
+    }
+
+    #[test]
+    fn king_centralises_in_endgame() {
+        let centre = from_fen("8/8/8/3k4/8/8/4P3/4K3 w - -");
+        let corner = from_fen("8/8/8/8/8/8/4P3/k3K3 w - -");
+
+        assert_eq!(game_phase(centre.material_key), 0);
+        assert!(evaluate_position(&centre, &EvalParams::default()) < evaluate_position(&corner, &EvalParams::default()));
+    }
+
+    #[test]
+    fn king_shelters_in_middlegame() {
+        let castled = from_fen("r1bq1rk1/pppppppp/2n2n2/8/8/2N2N2/PPPPPPPP/R1BQ1RK1 w - -");
+        let centre = from_fen("r1bq1r2/pppppppp/2nk1n2/8/8/2N2N2/PPPPPPPP/R1BQ1RK1 w - -");
+
+        assert!(evaluate_position(&castled, &EvalParams::default()) < evaluate_position(&centre, &EvalParams::default()));
+    }
+
+    #[test]
+    fn trace_adds_up_to_the_evaluation() {
+        let position = from_fen("r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - -");
+        let trace = evaluate_with_trace(&position, &EvalParams::default());
+
+        let expected = trace.terms().iter().fold(TaperedScore::default(), |score, term| score + term.white - term.black);
+        assert_eq!(trace.total, expected.taper(trace.phase));
+        assert_eq!(Score::cp(trace.total), evaluate_position(&position, &EvalParams::default()));
+        assert_eq!(trace.material.white, PieceValue::PAWN * 6 + PieceValue::KNIGHT * 2 + PieceValue::BISHOP + PieceValue::ROOK * 2 + PieceValue::QUEEN);
+    }
+
+    #[test]
+    fn elementary_endings_replace_the_terms() {
+        // a pawn up, but the defender has the opposition
+        let drawn = evaluate_with_trace(&from_fen("8/4k3/8/4K3/4P3/8/8/8 w - -"), &EvalParams::default());
+        assert_eq!((drawn.endgame, drawn.total), (Some(Endgame::Kpk), 0));
+        assert!(drawn.material.difference().eg > 0);
+
+        let middlegame = evaluate_with_trace(&from_fen("r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - -"), &EvalParams::default());
+        assert_eq!(middlegame.endgame, None);
+    }
+
+    #[test]
+    fn params_change_the_evaluation() {
+        let position = from_fen("4k3/8/8/8/8/8/3Q4/4K3 w - -");
+        let mut params = EvalParams::default();
+        params.material.values.queen = PieceValue::QUEEN * 2;
+
+        assert_eq!(evaluate_with_trace(&position, &params).material.white, PieceValue::QUEEN * 2);
+        assert!(evaluate_position(&position, &params) > evaluate_position(&position, &EvalParams::default()));
+    }
+
+    #[test]
+    fn extreme_weights_are_not_mates() {
+        let position = from_fen("4k3/p7/8/8/8/8/P2Q4/4K3 w - -");
+        let mut params = EvalParams::default();
+        params.material.values.queen = TaperedScore { mg: 10_000_000, eg: 10_000_000 };
+
+        let score = evaluate_position(&position, &params);
+        assert!(!score.is_mate());
+        assert_eq!(score, Score(Score::MATE_BOUND.0 - 1));
+    }
+
+    #[test]
+    fn loaded_tables_are_mirrored_for_black() {
+        let squares: Vec<i32> = (0..64).collect();
+        let json = format!(r#"{{"mg": {:?}, "eg": {:?}}}"#, squares, squares);
+        let table: PieceSquareTable = from_str(&json).unwrap();
+
+        // a1 for black is a8 for white
+        assert_eq!(table.bonus(Side::Black, 0), TaperedScore::new(56, 56));
+        assert_eq!(table.bonus(Side::White, 0), TaperedScore::new(0, 0));
+        assert_eq!(to_string(&table).unwrap(), json.replace(' ', ""));
+    }
+
+    #[test]
+    fn evaluation_is_colour_symmetric() {
+        let corpus = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq -",
+            "r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - -",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ -",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -",
+            "4k3/8/8/3N4/8/8/3Q4/4K3 b - -",
+            "8/5pk1/6p1/4P3/3B4/8/5PPP/6K1 w - -",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6",
+        ];
+
+        for fen in corpus {
+            let position = from_fen(fen);
+            let mirrored = position.mirrored();
+
+            assert_eq!(
+                evaluate_with_trace(&position, &EvalParams::default()).total,
+                -evaluate_with_trace(&mirrored, &EvalParams::default()).total,
+                "{}", fen,
+            );
+        }
+    }
+}
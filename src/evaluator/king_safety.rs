@@ -0,0 +1,184 @@
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::board_navigator::{adjacent_files_mask, file_mask, king_attacks, piece_attacks, rank_mask_of, relative_rank};
+use crate::pieces::{Pieces, Side};
+use crate::position::Position;
+
+use super::trace::SideScores;
+use super::TaperedScore;
+
+/// Centipawns in the middlegame and endgame, the king should come out in the endgame so these are middlegame only
+struct KingSafety;
+impl KingSafety {
+    /// for each friendly pawn on the rank in front of the king, on its file or an adjacent one
+    pub const SHIELD_CLOSE: TaperedScore = TaperedScore::new(12, 0);
+    /// for each friendly pawn two ranks in front of the king
+    pub const SHIELD_FAR: TaperedScore = TaperedScore::new(6, 0);
+    /// penalty by the sum of the attack weights of the pieces attacking the king zone
+    pub const DANGER: [i32; 16] = [0, 0, 2, 6, 12, 20, 30, 42, 56, 72, 90, 110, 132, 156, 182, 210];
+}
+
+/// How much a piece attacking squares next to the enemy king adds to the danger
+struct AttackWeight;
+impl AttackWeight {
+    pub const KNIGHT: i32 = 2;
+    pub const BISHOP: i32 = 2;
+    pub const ROOK: i32 = 3;
+    pub const QUEEN: i32 = 5;
+}
+
+/// A single attacker can rarely mate on its own, so the danger only counts from this many attackers
+const MIN_ATTACKERS: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct KingSafetyParams {
+    pub shield_close: TaperedScore,
+    pub shield_far: TaperedScore,
+    pub danger: [i32; 16],
+    pub knight_attack: i32,
+    pub bishop_attack: i32,
+    pub rook_attack: i32,
+    pub queen_attack: i32,
+}
+
+impl Default for KingSafetyParams {
+    fn default() -> KingSafetyParams {
+        KingSafetyParams {
+            shield_close: KingSafety::SHIELD_CLOSE,
+            shield_far: KingSafety::SHIELD_FAR,
+            danger: KingSafety::DANGER,
+            knight_attack: AttackWeight::KNIGHT,
+            bishop_attack: AttackWeight::BISHOP,
+            rook_attack: AttackWeight::ROOK,
+            queen_attack: AttackWeight::QUEEN,
+        }
+    }
+}
+
+/// Pawn shield and attacks on the king of each side
+pub fn evaluate_king_safety(position: &Position, params: &KingSafetyParams) -> SideScores {
+    SideScores::new(calc_king_safety(position, Side::White, params), calc_king_safety(position, Side::Black, params))
+}
+
+fn calc_king_safety(position: &Position, side: Side, params: &KingSafetyParams) -> TaperedScore {
+    let kings = position.get_coloured_pieces(side, Pieces::KING).0;
+    if kings == 0 {
+        return TaperedScore::default();
+    }
+    let king_square = kings.trailing_zeros() as usize;
+
+    calc_pawn_shield(position, side, king_square, params) - calc_king_danger(position, side, king_square, params)
+}
+
+/// Only a king that is still on its first two ranks is considered to be sheltering
+fn calc_pawn_shield(position: &Position, side: Side, king_square: usize, params: &KingSafetyParams) -> TaperedScore {
+    if relative_rank(side, king_square) > 1 {
+        return TaperedScore::default();
+    }
+
+    let file = king_square % 8;
+    let files = file_mask(file) | adjacent_files_mask(file);
+    let pawns = position.get_coloured_pieces(side, Pieces::PAWN).0 & files;
+
+    let close = pawns & rank_ahead(side, king_square, 1);
+    let far = pawns & rank_ahead(side, king_square, 2);
+
+    params.shield_close * close.count_ones() as i32 + params.shield_far * far.count_ones() as i32
+}
+
+/// Penalty from the enemy pieces attacking the king and the squares around it
+fn calc_king_danger(position: &Position, side: Side, king_square: usize, params: &KingSafetyParams) -> TaperedScore {
+    let enemy = side.opponent();
+    let occupancy = position.get_occupied().0;
+    let zone = king_attacks(king_square) | 1 << king_square;
+
+    let mut attackers = 0;
+    let mut units = 0;
+
+    let pieces = [
+        (Pieces::KNIGHT, params.knight_attack),
+        (Pieces::BISHOP, params.bishop_attack),
+        (Pieces::ROOK, params.rook_attack),
+        (Pieces::QUEEN, params.queen_attack),
+    ];
+
+    for (piece, weight) in pieces {
+        let mut bb = position.get_coloured_pieces(enemy, piece).0;
+        while bb != 0 {
+            let square = bb.trailing_zeros() as usize;
+            bb &= bb - 1;
+
+            if piece_attacks(piece, square, occupancy) & zone != 0 {
+                attackers += 1;
+                units += weight;
+            }
+        }
+    }
+
+    if attackers < MIN_ATTACKERS {
+        return TaperedScore::default();
+    }
+
+    let danger = params.danger[(units.max(0) as usize).min(params.danger.len() - 1)];
+    TaperedScore::new(danger, 0)
+}
+
+/// The whole rank `distance` ranks in front of the square, empty when that's off the board
+fn rank_ahead(side: Side, square: usize, distance: usize) -> u64 {
+    let row = square / 8;
+    match side {
+        Side::White if row >= distance => rank_mask_of(square - distance * 8),
+        Side::Black if row + distance <= 7 => rank_mask_of(square + distance * 8),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calc_king_danger, calc_pawn_shield, KingSafety, KingSafetyParams};
+    use crate::evaluator::TaperedScore;
+    use crate::fen::from_fen;
+    use crate::pieces::Side;
+
+    // g1
+    const WHITE_KING: usize = 62;
+
+    #[test]
+    fn pawn_shield_in_front_of_castled_king() {
+        let intact = from_fen("6k1/8/8/8/8/8/5PPP/6K1 w - -");
+        let advanced = from_fen("6k1/8/8/8/8/6P1/5P1P/6K1 w - -");
+
+        assert_eq!(calc_pawn_shield(&intact, Side::White, WHITE_KING, &KingSafetyParams::default()), KingSafety::SHIELD_CLOSE * 3);
+        assert_eq!(
+            calc_pawn_shield(&advanced, Side::White, WHITE_KING, &KingSafetyParams::default()),
+            KingSafety::SHIELD_CLOSE * 2 + KingSafety::SHIELD_FAR
+        );
+    }
+
+    #[test]
+    fn wandering_king_has_no_shield() {
+        let position = from_fen("6k1/8/8/8/8/5PPP/6K1/8 w - -");
+
+        // the king is on g2, so f3, g3 and h3 are close
+        assert_eq!(calc_pawn_shield(&position, Side::White, 54, &KingSafetyParams::default()), KingSafety::SHIELD_CLOSE * 3);
+
+        let position = from_fen("6k1/8/8/8/5PPP/6K1/8/8 w - -");
+        assert_eq!(calc_pawn_shield(&position, Side::White, 46, &KingSafetyParams::default()), TaperedScore::default());
+    }
+
+    #[test]
+    fn single_attacker_is_not_dangerous() {
+        let position = from_fen("6k1/8/8/8/8/8/5PPP/3q2K1 w - -");
+
+        assert_eq!(calc_king_danger(&position, Side::White, WHITE_KING, &KingSafetyParams::default()), TaperedScore::default());
+    }
+
+    #[test]
+    fn danger_grows_with_attackers() {
+        // queen on h4 and knight on g4 both hit squares next to the king
+        let position = from_fen("6k1/8/8/8/6nq/8/5PPP/6K1 w - -");
+
+        assert_eq!(calc_king_danger(&position, Side::White, WHITE_KING, &KingSafetyParams::default()), TaperedScore::new(KingSafety::DANGER[7], 0));
+    }
+}
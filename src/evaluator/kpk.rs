@@ -0,0 +1,204 @@
+use std::sync::OnceLock;
+
+/**
+ * Whether king and pawn against king is won, for every placement of the pieces and side to move
+ *
+ * The strong side plays up the board as white, with squares counted from a1 and the pawn on files a to d,
+ * the other files are mirrored onto them. Generated on first use by working back from the positions
+ * where the pawn promotes safely or is lost
+ */
+static BITBASE: OnceLock<Vec<u64>> = OnceLock::new();
+
+/// 2 sides to move, 24 pawn squares, 64 squares for each king
+const MAX_INDEX: usize = 2 * 24 * 64 * 64;
+
+// positions as they're worked out, as bits so that children can be or'd together
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+/// Whether the strong side wins, squares counted from a1 with the strong side playing up the board
+pub fn probe(strong_king: usize, pawn: usize, weak_king: usize, strong_to_move: bool) -> bool {
+    // the bitbase only has pawns on the queenside, the kingside is its mirror image
+    let (strong_king, pawn, weak_king) = match pawn % 8 {
+        0..=3 => (strong_king, pawn, weak_king),
+        _ => (strong_king ^ 7, pawn ^ 7, weak_king ^ 7),
+    };
+
+    let idx = index(strong_to_move, strong_king, weak_king, pawn);
+    let bitbase = BITBASE.get_or_init(generate);
+
+    bitbase[idx / 64] >> (idx % 64) & 1 == 1
+}
+
+fn index(white_to_move: bool, white_king: usize, black_king: usize, pawn: usize) -> usize {
+    white_king | black_king << 6 | (!white_to_move as usize) << 12 | (pawn % 8) << 13 | (6 - pawn / 8) << 15
+}
+
+/// Every position is settled once a pass leaves nothing that can still be worked out
+fn generate() -> Vec<u64> {
+    let positions: Vec<Position> = (0..MAX_INDEX).map(Position::new).collect();
+    let mut results: Vec<u8> = positions.iter().map(|position| position.initial_result()).collect();
+
+    let mut repeat = true;
+    while repeat {
+        repeat = false;
+        for idx in 0..MAX_INDEX {
+            if results[idx] == UNKNOWN {
+                results[idx] = positions[idx].classify(&results);
+                repeat |= results[idx] != UNKNOWN;
+            }
+        }
+    }
+
+    let mut bitbase = vec![0; MAX_INDEX / 64];
+    for (idx, result) in results.iter().enumerate() {
+        if *result == WIN {
+            bitbase[idx / 64] |= 1 << (idx % 64);
+        }
+    }
+
+    bitbase
+}
+
+struct Position {
+    white_to_move: bool,
+    white_king: usize,
+    black_king: usize,
+    pawn: usize,
+}
+
+impl Position {
+    fn new(idx: usize) -> Position {
+        Position {
+            white_king: idx & 0x3f,
+            black_king: (idx >> 6) & 0x3f,
+            white_to_move: (idx >> 12) & 1 == 0,
+            pawn: ((idx >> 13) & 0x3) + (6 - ((idx >> 15) & 0x7)) * 8,
+        }
+    }
+
+    /// The positions that are decided before any move is looked at
+    fn initial_result(&self) -> u8 {
+        let promotion = self.pawn + 8;
+
+        // pieces on the same square, or a king that can be taken
+        if distance(self.white_king, self.black_king) <= 1 ||
+            self.white_king == self.pawn ||
+            self.black_king == self.pawn ||
+            (self.white_to_move && pawn_attacks(self.pawn, self.black_king)) {
+            return INVALID;
+        }
+
+        // the pawn promotes without being taken
+        if self.white_to_move &&
+            self.pawn / 8 == 6 &&
+            self.white_king != promotion &&
+            (distance(self.black_king, promotion) > 1 || distance(self.white_king, promotion) == 1) {
+            return WIN;
+        }
+
+        // stalemate, or the pawn is taken
+        if !self.white_to_move {
+            let can_move = king_moves(self.black_king)
+                .any(|square| distance(square, self.white_king) > 1 && !pawn_attacks(self.pawn, square));
+            let takes_pawn = distance(self.black_king, self.pawn) == 1 && distance(self.white_king, self.pawn) > 1;
+
+            if !can_move || takes_pawn {
+                return DRAW;
+            }
+        }
+
+        UNKNOWN
+    }
+
+    /// White wins if any move wins and black draws if any move draws, the rest stays unknown while any
+    /// of its moves are
+    fn classify(&self, results: &[u8]) -> u8 {
+        let mut children = INVALID;
+
+        if self.white_to_move {
+            for square in king_moves(self.white_king) {
+                children |= results[index(false, square, self.black_king, self.pawn)];
+            }
+
+            // moves onto a king are invalid positions, which add nothing
+            if self.pawn / 8 < 6 {
+                children |= results[index(false, self.white_king, self.black_king, self.pawn + 8)];
+            }
+            if self.pawn / 8 == 1 && self.pawn + 8 != self.white_king && self.pawn + 8 != self.black_king {
+                children |= results[index(false, self.white_king, self.black_king, self.pawn + 16)];
+            }
+        } else {
+            for square in king_moves(self.black_king) {
+                children |= results[index(true, self.white_king, square, self.pawn)];
+            }
+        }
+
+        let (good, bad) = if self.white_to_move { (WIN, DRAW) } else { (DRAW, WIN) };
+        if children & good != 0 {
+            good
+        } else if children & UNKNOWN != 0 {
+            UNKNOWN
+        } else {
+            bad
+        }
+    }
+}
+
+fn distance(a: usize, b: usize) -> usize {
+    (a % 8).abs_diff(b % 8).max((a / 8).abs_diff(b / 8))
+}
+
+fn king_moves(square: usize) -> impl Iterator<Item = usize> {
+    (0..64).filter(move |to| distance(square, *to) == 1)
+}
+
+/// Whether the white pawn attacks the square
+fn pawn_attacks(pawn: usize, square: usize) -> bool {
+    square / 8 == pawn / 8 + 1 && (square % 8).abs_diff(pawn % 8) == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::probe;
+
+    /// Squares counted from a1
+    fn sq(name: &str) -> usize {
+        let bytes = name.as_bytes();
+        (bytes[1] - b'1') as usize * 8 + (bytes[0] - b'a') as usize
+    }
+
+    #[test]
+    fn known_wins_and_draws() {
+        // the king in front of its pawn wins with the opposition and draws without it
+        assert!(probe(sq("e5"), sq("e4"), sq("e7"), false));
+        assert!(!probe(sq("e5"), sq("e4"), sq("e7"), true));
+
+        // unless it's on the sixth rank, where it always wins
+        assert!(probe(sq("e6"), sq("e5"), sq("e8"), true));
+
+        // a rook pawn draws when the defender reaches the corner
+        assert!(!probe(sq("a6"), sq("a5"), sq("a8"), true));
+        assert!(!probe(sq("h6"), sq("h5"), sq("h8"), true));
+
+        // the defender can't catch the pawn
+        assert!(probe(sq("a1"), sq("d5"), sq("h6"), true));
+        assert!(!probe(sq("a1"), sq("d5"), sq("f6"), false));
+    }
+
+    #[test]
+    fn kingside_mirrors_the_queenside() {
+        for (strong, pawn, weak) in [("e5", "e4", "e7"), ("c6", "c5", "c8"), ("b1", "b4", "g7")] {
+            let mirror = |name: &str| sq(name) ^ 7;
+
+            for strong_to_move in [true, false] {
+                assert_eq!(
+                    probe(sq(strong), sq(pawn), sq(weak), strong_to_move),
+                    probe(mirror(strong), mirror(pawn), mirror(weak), strong_to_move),
+                );
+            }
+        }
+    }
+}
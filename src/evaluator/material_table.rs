@@ -0,0 +1,182 @@
+use std::cell::RefCell;
+
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::material::MaterialKey;
+use crate::pieces::{Pieces, Side};
+
+use super::endgames::{find_endgame, Endgame};
+use super::trace::SideScores;
+use super::TaperedScore;
+
+/// How much each piece counts towards the game phase, pawns and kings don't count
+struct PhaseWeight;
+impl PhaseWeight {
+    pub const BISHOP: i32 = 1;
+    pub const KNIGHT: i32 = 1;
+    pub const ROOK: i32 = 2;
+    pub const QUEEN: i32 = 4;
+}
+
+/// The phase with all the pieces on the board
+pub const MAX_PHASE: i32 = 24;
+
+/// Centipawns in the middlegame and endgame, per piece for each of its side's pawns over five
+struct Imbalance;
+impl Imbalance {
+    /// knights need outposts, which pawns give them
+    pub const KNIGHT_PAWNS: TaperedScore = TaperedScore::new(6, 6);
+    /// rooks need open files, which pawns take away
+    pub const ROOK_PAWNS: TaperedScore = TaperedScore::new(-12, -12);
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct ImbalanceParams {
+    pub knight_pawns: TaperedScore,
+    pub rook_pawns: TaperedScore,
+}
+
+impl Default for ImbalanceParams {
+    fn default() -> ImbalanceParams {
+        ImbalanceParams {
+            knight_pawns: Imbalance::KNIGHT_PAWNS,
+            rook_pawns: Imbalance::ROOK_PAWNS,
+        }
+    }
+}
+
+impl ImbalanceParams {
+    /// Hash of the weights, mixed into the material key so entries for other params aren't reused
+    fn fingerprint(&self) -> u64 {
+        [self.knight_pawns, self.rook_pawns].into_iter().fold(0xCBF2_9CE4_8422_2325, |hash, weight| {
+            let hash = (hash ^ weight.mg as u32 as u64).wrapping_mul(0x100_0000_01B3);
+            (hash ^ weight.eg as u32 as u64).wrapping_mul(0x100_0000_01B3)
+        })
+    }
+}
+
+/**
+ * Everything the evaluation needs that depends on the material alone, worked out once per material
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct MaterialEntry {
+    key: u64,
+    pub phase: i32,
+    pub imbalance: SideScores,
+    /// the elementary ending for the material and the side trying to win it
+    pub endgame: Option<(Endgame, Side)>,
+}
+
+const MATERIAL_TABLE_SIZE: usize = 8192;
+
+struct MaterialTable {
+    entries: Vec<Option<MaterialEntry>>,
+}
+
+impl MaterialTable {
+    fn new() -> MaterialTable {
+        MaterialTable {
+            entries: vec![None; MATERIAL_TABLE_SIZE],
+        }
+    }
+
+    fn probe(&self, key: u64) -> Option<MaterialEntry> {
+        match self.entries[index(key)] {
+            Some(entry) if entry.key == key => Some(entry),
+            _ => None,
+        }
+    }
+
+    fn store(&mut self, entry: MaterialEntry) {
+        self.entries[index(entry.key)] = Some(entry);
+    }
+}
+
+/// Material keys are counts rather than random, so they're mixed to spread them over the table
+fn index(key: u64) -> usize {
+    (key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as usize % MATERIAL_TABLE_SIZE
+}
+
+thread_local! {
+    // one per thread so that searches running in parallel don't have to share it
+    static MATERIAL_TABLE: RefCell<MaterialTable> = RefCell::new(MaterialTable::new());
+}
+
+pub fn probe_material(material: MaterialKey, params: &ImbalanceParams) -> MaterialEntry {
+    let key = material.0 ^ params.fingerprint();
+
+    if let Some(entry) = MATERIAL_TABLE.with(|table| table.borrow().probe(key)) {
+        return entry;
+    }
+
+    let entry = MaterialEntry {
+        key,
+        phase: game_phase(material),
+        imbalance: SideScores::new(calc_imbalance(material, Side::White, params), calc_imbalance(material, Side::Black, params)),
+        endgame: find_endgame(material),
+    };
+
+    MATERIAL_TABLE.with(|table| table.borrow_mut().store(entry));
+
+    entry
+}
+
+/// From MAX_PHASE with all the pieces on the board down to 0 when only kings and pawns are left.
+/// Promotions can take it above MAX_PHASE, so it's capped.
+pub fn game_phase(material: MaterialKey) -> i32 {
+    let phase = [Side::White, Side::Black].into_iter()
+        .map(|side| {
+            material.count(side, Pieces::BISHOP) as i32 * PhaseWeight::BISHOP +
+                material.count(side, Pieces::KNIGHT) as i32 * PhaseWeight::KNIGHT +
+                material.count(side, Pieces::ROOK) as i32 * PhaseWeight::ROOK +
+                material.count(side, Pieces::QUEEN) as i32 * PhaseWeight::QUEEN
+        })
+        .sum::<i32>();
+
+    phase.min(MAX_PHASE)
+}
+
+fn calc_imbalance(material: MaterialKey, side: Side, params: &ImbalanceParams) -> TaperedScore {
+    let extra_pawns = material.count(side, Pieces::PAWN) as i32 - 5;
+
+    params.knight_pawns * (material.count(side, Pieces::KNIGHT) as i32 * extra_pawns) +
+        params.rook_pawns * (material.count(side, Pieces::ROOK) as i32 * extra_pawns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{probe_material, ImbalanceParams, Imbalance};
+    use crate::evaluator::Endgame;
+    use crate::fen::from_fen;
+    use crate::pieces::Side;
+
+    #[test]
+    fn knights_like_pawns_and_rooks_dont() {
+        let params = ImbalanceParams::default();
+        let closed = probe_material(from_fen("4k3/pppppppp/8/8/8/8/PPPPPPPP/1N2K2R w - -").material_key, &params);
+        let open = probe_material(from_fen("4k3/pp6/8/8/8/8/PP6/1N2K2R w - -").material_key, &params);
+
+        assert_eq!(closed.imbalance.white, Imbalance::KNIGHT_PAWNS * 3 + Imbalance::ROOK_PAWNS * 3);
+        assert_eq!(open.imbalance.white, Imbalance::KNIGHT_PAWNS * -3 + Imbalance::ROOK_PAWNS * -3);
+        assert_eq!(closed.imbalance.black, Default::default());
+    }
+
+    #[test]
+    fn entries_follow_the_params() {
+        let material = from_fen("4k3/pppppppp/8/8/8/8/PPPPPPPP/1N2K3 w - -").material_key;
+        let mut params = ImbalanceParams::default();
+        let before = probe_material(material, &params);
+
+        params.knight_pawns = Imbalance::KNIGHT_PAWNS * 2;
+        assert_eq!(probe_material(material, &params).imbalance.white, before.imbalance.white * 2);
+    }
+
+    #[test]
+    fn endgame_is_found_by_material() {
+        let entry = probe_material(from_fen("8/8/8/4p3/4k3/8/4K3/8 w - -").material_key, &ImbalanceParams::default());
+
+        assert_eq!(entry.endgame, Some((Endgame::Kpk, Side::Black)));
+        assert_eq!(entry.phase, 0);
+    }
+}
@@ -0,0 +1,115 @@
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::board_navigator::{pawn_attacks, piece_attacks};
+use crate::pieces::{Pieces, Side};
+use crate::position::Position;
+
+use super::trace::SideScores;
+use super::TaperedScore;
+
+/**
+ * Centipawns for each square a piece can move to above (or below) the typical number for that piece,
+ * squares occupied by friendly pieces or attacked by enemy pawns don't count
+ */
+struct Mobility;
+impl Mobility {
+    pub const KNIGHT: TaperedScore = TaperedScore::new(4, 4);
+    pub const BISHOP: TaperedScore = TaperedScore::new(5, 5);
+    pub const ROOK: TaperedScore = TaperedScore::new(2, 4);
+    pub const QUEEN: TaperedScore = TaperedScore::new(1, 2);
+}
+
+/// Centipawns per square of mobility
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct MobilityParams {
+    pub knight: TaperedScore,
+    pub bishop: TaperedScore,
+    pub rook: TaperedScore,
+    pub queen: TaperedScore,
+}
+
+impl Default for MobilityParams {
+    fn default() -> MobilityParams {
+        MobilityParams {
+            knight: Mobility::KNIGHT,
+            bishop: Mobility::BISHOP,
+            rook: Mobility::ROOK,
+            queen: Mobility::QUEEN,
+        }
+    }
+}
+
+/// Squares a piece typically has, so that mobility is zero on average and doesn't inflate material
+struct TypicalMobility;
+impl TypicalMobility {
+    pub const KNIGHT: i32 = 4;
+    pub const BISHOP: i32 = 6;
+    pub const ROOK: i32 = 7;
+    pub const QUEEN: i32 = 13;
+}
+
+/// Mobility of the knights, bishops, rooks and queens of each side
+pub fn evaluate_mobility(position: &Position, params: &MobilityParams) -> SideScores {
+    SideScores::new(calc_mobility(position, Side::White, params), calc_mobility(position, Side::Black, params))
+}
+
+fn calc_mobility(position: &Position, side: Side, params: &MobilityParams) -> TaperedScore {
+    let occupancy = position.get_occupied().0;
+    let enemy_pawns = position.get_coloured_pieces(side.opponent(), Pieces::PAWN).0;
+    let area = !position.get_side_pieces(side).0 & !pawn_attacks(side.opponent(), enemy_pawns);
+
+    let mut score = TaperedScore::default();
+
+    let pieces = [
+        (Pieces::KNIGHT, params.knight, TypicalMobility::KNIGHT),
+        (Pieces::BISHOP, params.bishop, TypicalMobility::BISHOP),
+        (Pieces::ROOK, params.rook, TypicalMobility::ROOK),
+        (Pieces::QUEEN, params.queen, TypicalMobility::QUEEN),
+    ];
+
+    for (piece, weight, typical) in pieces {
+        let mut bb = position.get_coloured_pieces(side, piece).0;
+        while bb != 0 {
+            let square = bb.trailing_zeros() as usize;
+            bb &= bb - 1;
+
+            let moves = (piece_attacks(piece, square, occupancy) & area).count_ones() as i32;
+            score += weight * (moves - typical);
+        }
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calc_mobility, evaluate_mobility, Mobility, MobilityParams, TypicalMobility};
+    use crate::fen::from_fen;
+    use crate::pieces::Side;
+
+    #[test]
+    fn centralised_knight_is_more_mobile() {
+        let centre = from_fen("4k3/8/8/8/3N4/8/8/4K3 w - -");
+        let corner = from_fen("4k3/8/8/8/8/8/8/N3K3 w - -");
+
+        assert_eq!(calc_mobility(&centre, Side::White, &MobilityParams::default()), Mobility::KNIGHT * (8 - TypicalMobility::KNIGHT));
+        assert_eq!(calc_mobility(&corner, Side::White, &MobilityParams::default()), Mobility::KNIGHT * (2 - TypicalMobility::KNIGHT));
+    }
+
+    #[test]
+    fn squares_attacked_by_enemy_pawns_are_not_counted() {
+        // b6 and f6 are covered by the a7 and g7 pawns
+        let position = from_fen("4k3/p5p1/8/3N4/8/8/8/4K3 w - -");
+
+        assert_eq!(calc_mobility(&position, Side::White, &MobilityParams::default()), Mobility::KNIGHT * (6 - TypicalMobility::KNIGHT));
+    }
+
+    #[test]
+    fn start_position_is_level() {
+        let position = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+
+        let scores = evaluate_mobility(&position, &MobilityParams::default());
+        assert_eq!(scores.white, scores.black);
+    }
+}
@@ -0,0 +1,23 @@
+// ================
+// Evaluator Module
+// ================
+
+// modules
+mod activity;
+mod endgames;
+mod eval;
+mod king_safety;
+mod kpk;
+mod material_table;
+mod mobility;
+mod params;
+mod pawns;
+mod trace;
+
+// exports from module
+pub use endgames::Endgame;
+pub use eval::evaluate_position;
+pub use eval::evaluate_with_trace;
+pub use eval::TaperedScore;
+pub use params::EvalParams;
+pub use trace::EvalTrace;
@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::Path;
+
+use rocket::fairing::AdHoc;
+use rocket::figment::providers::{Format, Toml};
+use rocket::figment::Figment;
+use rocket::serde::{json, Deserialize, Serialize};
+
+use super::activity::ActivityParams;
+use super::eval::MaterialParams;
+use super::king_safety::KingSafetyParams;
+use super::material_table::ImbalanceParams;
+use super::mobility::MobilityParams;
+use super::pawns::PawnParams;
+
+/**
+ * Every weight of the evaluation, the defaults are the built in constants
+ *
+ * Missing fields take their default, so a file only needs the weights being experimented with
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct EvalParams {
+    pub material: MaterialParams,
+    pub imbalance: ImbalanceParams,
+    pub pawns: PawnParams,
+    pub mobility: MobilityParams,
+    pub activity: ActivityParams,
+    pub king_safety: KingSafetyParams,
+}
+
+impl EvalParams {
+    /// Reads a .json or .toml file
+    pub fn from_file(path: &Path) -> Result<EvalParams, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Can't read evaluation parameters from {}: {}", path.display(), e))?;
+
+        let params = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => json::from_str(&contents).map_err(|e| e.to_string()),
+            Some("toml") => Figment::from(Toml::string(&contents)).extract().map_err(|e| e.to_string()),
+            _ => Err(String::from("Evaluation parameters must be a .json or .toml file")),
+        };
+
+        params.map_err(|e| format!("Invalid evaluation parameters in {}: {}", path.display(), e))
+    }
+
+    /// Manages the parameters from the file at `eval_params` in the Rocket config, or the defaults
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Evaluation parameters", |rocket| async {
+            let params = match rocket.figment().extract_inner::<String>("eval_params") {
+                Ok(path) => EvalParams::from_file(Path::new(&path)),
+                Err(_) => Ok(EvalParams::default()),
+            };
+
+            match params {
+                Ok(params) => Ok(rocket.manage(params)),
+                Err(error) => {
+                    rocket::error!("{}", error);
+                    Err(rocket)
+                },
+            }
+        })
+    }
+}
+
+/**
+ * (De)serializes a table with a value per square, serde only derives arrays up to 32 long
+ */
+pub mod square_table {
+    use rocket::serde::de::Error;
+    use rocket::serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(table: &[i32; 64], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(table)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[i32; 64], D::Error> {
+        let squares = Vec::<i32>::deserialize(deserializer)?;
+        let count = squares.len();
+        squares.try_into().map_err(|_| D::Error::custom(format!("expected 64 squares, found {}", count)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::EvalParams;
+    use crate::evaluator::TaperedScore;
+
+    fn write_temp(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn partial_json_keeps_other_defaults() {
+        let path = write_temp("params.json", r#"{"activity": {"bishop_pair": {"mg": 10, "eg": 20}}}"#);
+
+        let params = EvalParams::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(params.activity.bishop_pair, TaperedScore::new(10, 20));
+        assert_eq!(params.pawns, EvalParams::default().pawns);
+    }
+
+    #[test]
+    fn toml_overrides_a_weight() {
+        let path = write_temp("params.toml", "[pawns]\ndoubled = { mg = -1, eg = -2 }\n");
+
+        let params = EvalParams::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(params.pawns.doubled, TaperedScore::new(-1, -2));
+    }
+
+    #[test]
+    fn tables_must_cover_the_board() {
+        let path = write_temp("short.json", r#"{"material": {"pst": {"pawn": {"mg": [1, 2, 3]}}}}"#);
+
+        let error = EvalParams::from_file(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        assert!(error.contains("expected 64 squares, found 3"), "{}", error);
+    }
+
+    #[test]
+    fn defaults_survive_serialization() {
+        let params = EvalParams::default();
+        let json = rocket::serde::json::to_string(&params).unwrap();
+
+        assert_eq!(rocket::serde::json::from_str::<EvalParams>(&json).unwrap(), params);
+    }
+}
@@ -0,0 +1,283 @@
+use std::cell::RefCell;
+
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::board_navigator::{adjacent_files_mask, file_mask, pawn_attacks, rank_mask_of, relative_rank};
+use crate::pieces::Side;
+use crate::position::Position;
+use crate::zobrist::pawn_key;
+
+use super::trace::SideScores;
+use super::TaperedScore;
+
+/// The ranks in front of the square from the side's point of view, bit 0 is a8 so white pawns move towards it
+fn forward_ranks_mask(side: Side, square: usize) -> u64 {
+    let row = square / 8;
+    match side {
+        Side::White => (1u64 << (row * 8)) - 1,
+        Side::Black => if row == 7 { 0 } else { !((1u64 << ((row + 1) * 8)) - 1) },
+    }
+}
+
+/// The rank of the square and the ranks behind it from the side's point of view
+fn rear_ranks_mask(side: Side, square: usize) -> u64 {
+    !forward_ranks_mask(side, square)
+}
+
+/// The square in front of the pawn, pawns on the promotion rank don't exist so there always is one
+fn stop_square(side: Side, square: usize) -> usize {
+    match side {
+        Side::White => square - 8,
+        Side::Black => square + 8,
+    }
+}
+
+/// Centipawns in the middlegame and endgame
+struct PawnStructure;
+impl PawnStructure {
+    /// for each pawn on a file after the first
+    pub const DOUBLED: TaperedScore = TaperedScore::new(-10, -25);
+    /// no friendly pawns on the adjacent files
+    pub const ISOLATED: TaperedScore = TaperedScore::new(-8, -12);
+    /// can't be supported by friendly pawns and can't advance safely
+    pub const BACKWARD: TaperedScore = TaperedScore::new(-6, -10);
+    /// by relative rank, no enemy pawns can stop it from promoting
+    pub const PASSED: [TaperedScore; 8] = [
+        TaperedScore::new(0, 0),
+        TaperedScore::new(5, 10),
+        TaperedScore::new(10, 17),
+        TaperedScore::new(15, 25),
+        TaperedScore::new(30, 50),
+        TaperedScore::new(55, 90),
+        TaperedScore::new(90, 140),
+        TaperedScore::new(0, 0),
+    ];
+    /// by relative rank, defended by or side by side with a friendly pawn
+    pub const CONNECTED: [TaperedScore; 8] = [
+        TaperedScore::new(0, 0),
+        TaperedScore::new(4, 2),
+        TaperedScore::new(6, 4),
+        TaperedScore::new(10, 7),
+        TaperedScore::new(18, 14),
+        TaperedScore::new(30, 25),
+        TaperedScore::new(50, 45),
+        TaperedScore::new(0, 0),
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct PawnParams {
+    pub doubled: TaperedScore,
+    pub isolated: TaperedScore,
+    pub backward: TaperedScore,
+    pub passed: [TaperedScore; 8],
+    pub connected: [TaperedScore; 8],
+}
+
+impl Default for PawnParams {
+    fn default() -> PawnParams {
+        PawnParams {
+            doubled: PawnStructure::DOUBLED,
+            isolated: PawnStructure::ISOLATED,
+            backward: PawnStructure::BACKWARD,
+            passed: PawnStructure::PASSED,
+            connected: PawnStructure::CONNECTED,
+        }
+    }
+}
+
+impl PawnParams {
+    /// Hash of the weights, so that the pawn table doesn't return scores from other parameters
+    fn fingerprint(&self) -> u64 {
+        let weights = [self.doubled, self.isolated, self.backward].into_iter()
+            .chain(self.passed)
+            .chain(self.connected);
+
+        weights.fold(0xCBF2_9CE4_8422_2325, |hash, weight| {
+            let hash = (hash ^ weight.mg as u32 as u64).wrapping_mul(0x100_0000_01B3);
+            (hash ^ weight.eg as u32 as u64).wrapping_mul(0x100_0000_01B3)
+        })
+    }
+}
+
+const PAWN_TABLE_SIZE: usize = 16384;
+
+#[derive(Clone, Copy)]
+struct PawnEntry {
+    key: u64,
+    scores: SideScores,
+}
+
+/**
+ * Pawn structure scores by pawn key, pawns move rarely so most positions in a search share a few structures
+ */
+struct PawnTable {
+    entries: Vec<Option<PawnEntry>>,
+}
+
+impl PawnTable {
+    fn new() -> PawnTable {
+        PawnTable {
+            entries: vec![None; PAWN_TABLE_SIZE],
+        }
+    }
+
+    fn probe(&self, key: u64) -> Option<SideScores> {
+        match self.entries[key as usize % PAWN_TABLE_SIZE] {
+            Some(entry) if entry.key == key => Some(entry.scores),
+            _ => None,
+        }
+    }
+
+    fn store(&mut self, key: u64, scores: SideScores) {
+        self.entries[key as usize % PAWN_TABLE_SIZE] = Some(PawnEntry { key, scores });
+    }
+}
+
+thread_local! {
+    // one per thread so that searches running in parallel don't have to share it
+    static PAWN_TABLE: RefCell<PawnTable> = RefCell::new(PawnTable::new());
+}
+
+/// Pawn structure of each side
+pub fn evaluate_pawns(position: &Position, params: &PawnParams) -> SideScores {
+    let key = pawn_key(position) ^ params.fingerprint();
+
+    if let Some(scores) = PAWN_TABLE.with(|table| table.borrow().probe(key)) {
+        return scores;
+    }
+
+    let white_pawns = position.get_white_pawns().0;
+    let black_pawns = position.get_black_pawns().0;
+    let scores = SideScores::new(
+        calc_pawn_structure(Side::White, white_pawns, black_pawns, params),
+        calc_pawn_structure(Side::Black, black_pawns, white_pawns, params),
+    );
+
+    PAWN_TABLE.with(|table| table.borrow_mut().store(key, scores));
+
+    scores
+}
+
+fn calc_pawn_structure(side: Side, own_pawns: u64, enemy_pawns: u64, params: &PawnParams) -> TaperedScore {
+    let mut score = TaperedScore::default();
+    let own_attacks = pawn_attacks(side, own_pawns);
+    let enemy_attacks = pawn_attacks(side.opponent(), enemy_pawns);
+
+    for file in 0..8 {
+        let pawns_on_file = (own_pawns & file_mask(file)).count_ones() as i32;
+        if pawns_on_file > 1 {
+            score += params.doubled * (pawns_on_file - 1);
+        }
+    }
+
+    let mut pawns = own_pawns;
+    while pawns != 0 {
+        let square = pawns.trailing_zeros() as usize;
+        pawns &= pawns - 1;
+
+        let file = square % 8;
+        let rank = relative_rank(side, square);
+        let forward = forward_ranks_mask(side, square);
+        let neighbours = own_pawns & adjacent_files_mask(file);
+
+        let is_passed = enemy_pawns & forward & (file_mask(file) | adjacent_files_mask(file)) == 0;
+        // only the front pawn of doubled pawns counts as passed
+        let is_blocked_by_own = own_pawns & forward & file_mask(file) != 0;
+        if is_passed && !is_blocked_by_own {
+            score += params.passed[rank];
+        }
+
+        if neighbours == 0 {
+            score += params.isolated;
+        } else {
+            let can_be_supported = neighbours & rear_ranks_mask(side, square) != 0;
+            let is_stop_attacked = enemy_attacks >> stop_square(side, square) & 1 == 1;
+            if !can_be_supported && is_stop_attacked {
+                score += params.backward;
+            }
+        }
+
+        let is_defended = own_attacks >> square & 1 == 1;
+        let has_phalanx = neighbours & rank_mask_of(square) != 0;
+        if is_defended || has_phalanx {
+            score += params.connected[rank];
+        }
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calc_pawn_structure, evaluate_pawns, PawnParams, PawnStructure};
+    use crate::evaluator::TaperedScore;
+    use crate::fen::from_fen;
+    use crate::pieces::Side;
+
+    fn white_structure(fen: &str) -> TaperedScore {
+        let position = from_fen(fen);
+        calc_pawn_structure(Side::White, position.get_white_pawns().0, position.get_black_pawns().0, &PawnParams::default())
+    }
+
+    #[test]
+    fn passed_isolated_pawn() {
+        let score = white_structure("4k3/8/3P4/8/8/8/8/4K3 w - -");
+
+        assert_eq!(score, PawnStructure::PASSED[5] + PawnStructure::ISOLATED);
+    }
+
+    #[test]
+    fn pawn_is_not_passed_with_enemy_on_adjacent_file_ahead() {
+        let score = white_structure("4k3/2p5/8/3P4/8/8/8/4K3 w - -");
+
+        assert_eq!(score, PawnStructure::ISOLATED);
+    }
+
+    #[test]
+    fn doubled_pawns() {
+        let score = white_structure("4k3/p7/8/8/8/3P4/3P4/4K3 w - -");
+
+        assert_eq!(score, PawnStructure::DOUBLED + PawnStructure::ISOLATED + PawnStructure::ISOLATED + PawnStructure::PASSED[2]);
+    }
+
+    #[test]
+    fn connected_pawns() {
+        let score = white_structure("4k3/ppp5/8/8/8/8/4PP2/4K3 w - -");
+
+        assert_eq!(score, PawnStructure::PASSED[1] + PawnStructure::PASSED[1] + PawnStructure::CONNECTED[1] + PawnStructure::CONNECTED[1]);
+    }
+
+    #[test]
+    fn backward_pawn() {
+        // d3 can't be defended by the c4 and e4 pawns and d4 is attacked by the c5 pawn
+        let score = white_structure("4k3/8/8/2p5/2P1P3/3P4/8/4K3 w - -");
+        let expected = PawnStructure::BACKWARD +
+            PawnStructure::CONNECTED[3] + PawnStructure::CONNECTED[3] + // c4 and e4 are defended by d3
+            PawnStructure::PASSED[3]; // e4
+
+        assert_eq!(score, expected);
+    }
+
+    #[test]
+    fn symmetrical_structures_cancel_out() {
+        let position = from_fen("4k3/pp3ppp/2p5/3p4/3P4/2P5/PP3PPP/4K3 w - -");
+
+        let scores = evaluate_pawns(&position, &PawnParams::default());
+        assert_eq!(scores.white, scores.black);
+        // and again from the cache
+        assert_eq!(evaluate_pawns(&position, &PawnParams::default()), scores);
+    }
+
+    #[test]
+    fn cached_scores_are_not_shared_between_params() {
+        let position = from_fen("4k3/8/8/8/8/3P4/3P4/4K3 w - -");
+        let params = PawnParams { doubled: TaperedScore::default(), ..PawnParams::default() };
+
+        let default_scores = evaluate_pawns(&position, &PawnParams::default());
+        let scores = evaluate_pawns(&position, &params);
+
+        assert_eq!(default_scores.white - scores.white, PawnStructure::DOUBLED);
+    }
+}
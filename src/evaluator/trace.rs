@@ -0,0 +1,56 @@
+use rocket::serde::Serialize;
+
+use super::endgames::Endgame;
+use super::TaperedScore;
+
+/**
+ * One term of the evaluation for each side, the term counts white minus black
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SideScores {
+    pub white: TaperedScore,
+    pub black: TaperedScore,
+}
+
+impl SideScores {
+    pub fn new(white: TaperedScore, black: TaperedScore) -> SideScores {
+        SideScores { white, black }
+    }
+
+    pub fn difference(self) -> TaperedScore {
+        self.white - self.black
+    }
+}
+
+/**
+ * Every term that went into an evaluation, before tapering
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct EvalTrace {
+    /// from 24 with all the pieces on the board to 0 with only kings and pawns
+    pub phase: i32,
+    /// piece values
+    pub material: SideScores,
+    /// piece-square table bonuses
+    pub pst: SideScores,
+    /// piece values adjusted for the rest of the material, knights gain and rooks lose with more pawns
+    pub imbalance: SideScores,
+    pub pawns: SideScores,
+    pub mobility: SideScores,
+    /// rooks on open files and the bishop pair
+    pub activity: SideScores,
+    pub king_safety: SideScores,
+    /// the elementary ending that was evaluated instead of the terms, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endgame: Option<Endgame>,
+    /// centipawns, positive when white is better
+    pub total: i32,
+}
+
+impl EvalTrace {
+    pub fn terms(&self) -> [SideScores; 7] {
+        [self.material, self.pst, self.imbalance, self.pawns, self.mobility, self.activity, self.king_safety]
+    }
+}
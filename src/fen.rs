@@ -2,7 +2,7 @@ use crate::pieces::{PieceAsci, Pieces, Side};
 use crate::position::{StatelessPosition, Position, Castling};
 use crate::bitboard::BitBoard;
 use crate::utils::get_uni_char;
-use crate::board_navigator::Coord;
+use crate::board_navigator::{Coord, is_in_check};
 
 use std::collections::HashMap;
 
@@ -94,12 +94,123 @@ pub fn to_fen(position: Position) -> String {
     fen
 }
 
+/// Builds a position from a FEN that is known to be valid, panicking otherwise
 pub fn from_fen(fen: &str) -> Position {
-    let [placement, active_color, castling, en_passant_target/*, _half_move_clock, _full_moves*/]: [&str; 4/*6*/] = fen.split(' ')
-        .collect::<Vec<&str>>()
-        .try_into()
-        .unwrap();
-    build_position(placement, active_color, castling, en_passant_target, 0, 1) // TODO get _half_move_clock and _full_moves working in client app
+    match try_from_fen(fen) {
+        Ok(position) => position,
+        Err(error) => panic!("Invalid FEN '{}': {}", fen, error),
+    }
+}
+
+/// Builds a position from a FEN, with or without the half move clock and full move number,
+/// returning why the FEN is invalid rather than panicking
+pub fn try_from_fen(fen: &str) -> Result<Position, String> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+
+    let (half_move_clock, full_moves) = match fields.len() {
+        4 => (0, 1), // the client app doesn't send the clocks
+        6 => (
+            fields[4].parse::<usize>().map_err(|_| format!("Invalid half move clock '{}'", fields[4]))?,
+            fields[5].parse::<usize>().map_err(|_| format!("Invalid full move number '{}'", fields[5]))?,
+        ),
+        count => return Err(format!("Expected 4 or 6 fields but found {}", count)),
+    };
+
+    let [placement, active_colour, castling, en_passant_target] = [fields[0], fields[1], fields[2], fields[3]];
+
+    validate_placement(placement)?;
+    validate_piece_counts(placement)?;
+
+    if active_colour != "w" && active_colour != "b" {
+        return Err(format!("Unknown active colour '{}'", active_colour));
+    }
+
+    if castling != "-" && (castling.is_empty() || !castling.chars().all(|c| "KQkq".contains(c))) {
+        return Err(format!("Invalid castling rights '{}'", castling));
+    }
+
+    validate_en_passant_target(en_passant_target, active_colour)?;
+
+    let position = build_position(placement, active_colour, castling, en_passant_target, half_move_clock, full_moves);
+
+    validate_kings(&position)?;
+
+    Ok(position)
+}
+
+fn validate_placement(placement: &str) -> Result<(), String> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+
+    if ranks.len() != 8 {
+        return Err(format!("Expected 8 ranks but found {}", ranks.len()));
+    }
+
+    for (i, rank) in ranks.iter().enumerate() {
+        let rank_number = 8 - i;
+        let mut squares = 0;
+
+        for c in rank.chars() {
+            match c {
+                '1'..='8' => squares += c.to_digit(10).unwrap(),
+                'p' | 'P' if rank_number == 1 || rank_number == 8 => {
+                    return Err(format!("Pawn on rank {}", rank_number));
+                },
+                'p' | 'n' | 'b' | 'r' | 'q' | 'k' | 'P' | 'N' | 'B' | 'R' | 'Q' | 'K' => squares += 1,
+                _ => return Err(format!("Unknown piece '{}' on rank {}", c, rank_number)),
+            }
+        }
+
+        if squares != 8 {
+            return Err(format!("Rank {} has {} squares", rank_number, squares));
+        }
+    }
+
+    Ok(())
+}
+
+/// The target is behind a pawn that has just moved 2 squares, so it's on the 3rd rank when black is to move
+fn validate_en_passant_target(en_passant_target: &str, active_colour: &str) -> Result<(), String> {
+    if en_passant_target == "-" {
+        return Ok(());
+    }
+
+    let expected_rank = if active_colour == "b" { '3' } else { '6' };
+    let chars: Vec<char> = en_passant_target.chars().collect();
+
+    match chars[..] {
+        [file, rank] if ('a'..='h').contains(&file) && rank == expected_rank => Ok(()),
+        _ => Err(format!("Invalid en passant target '{}'", en_passant_target)),
+    }
+}
+
+/// No more of a piece than promoting every pawn could give, which also keeps each count within `MaterialKey`
+fn validate_piece_counts(placement: &str) -> Result<(), String> {
+    for (symbol, max) in [('P', 8), ('N', 10), ('B', 10), ('R', 10), ('Q', 9)] {
+        for symbol in [symbol, symbol.to_ascii_lowercase()] {
+            let count = placement.chars().filter(|c| *c == symbol).count();
+
+            if count > max {
+                return Err(format!("Too many '{}', found {} but at most {} are possible", symbol, count, max));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_kings(position: &Position) -> Result<(), String> {
+    if position.get_white_kings().count() != 1 || position.get_black_kings().count() != 1 {
+        return Err(String::from("Each side must have exactly one king"));
+    }
+
+    // the side that has just moved can't have left its king in check
+    let mut opponent = position.clone();
+    opponent.active_colour = opponent.active_colour.opponent();
+    if is_in_check(&opponent) {
+        return Err(String::from("The side not to move is in check"));
+    }
+
+    Ok(())
 }
 
 pub fn fen_to_asci_board(fen: String) -> String {
@@ -182,9 +293,6 @@ fn get_en_passant_target_coord(en_passant_target: &str) -> Option<Coord> {
     let file = en_passant_target_chars.next().unwrap();
     let rank_char = en_passant_target_chars.next().unwrap();
 
-    println!("en_passant_target: '{}'", en_passant_target);
-    println!("File: {}, Rank: {}", file, rank_char);
-
     const RADIX: u32 = 10;
     let rank = rank_char.to_digit(RADIX).unwrap() as u8;
 
@@ -240,3 +348,36 @@ fn build_stateless_position(fen: &str) -> StatelessPosition {
     StatelessPosition::new(white_pieces, black_pieces, pawns, bishops, knights, rooks, queens, kings)
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::try_from_fen;
+
+    #[test]
+    fn accepts_fen_with_clocks() {
+        let position = try_from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+        assert_eq!(position.half_move_clock, 0);
+        assert_eq!(position.full_moves, 1);
+    }
+
+    #[test]
+    fn rejects_invalid_fens() {
+        for (fen, error) in [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq -", "Expected 8 ranks but found 7"),
+            ("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -", "Unknown piece '9' on rank 6"),
+            ("rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -", "Rank 7 has 7 squares"),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq -", "Unknown active colour 'x'"),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQxq -", "Invalid castling rights 'KQxq'"),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3", "Invalid en passant target 'e3'"),
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1", "Invalid half move clock 'x'"),
+            ("rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ -", "Each side must have exactly one king"),
+            ("4k3/8/8/8/8/8/8/4RK2 w - -", "The side not to move is in check"),
+            ("Pnbqkbnr/pppppppp/8/8/8/8/1PPPPPPP/RNBQKBNR w KQkq -", "Pawn on rank 8"),
+            ("4k3/8/8/NNNNNNNN/NNNNNNNN/8/8/4K3 w - -", "Too many 'N', found 16 but at most 10 are possible"),
+            ("4k3/qqqqqqqq/qq6/8/8/8/8/4K3 w - -", "Too many 'q', found 10 but at most 9 are possible"),
+            ("", "Expected 4 or 6 fields but found 0"),
+        ] {
+            assert_eq!(try_from_fen(fen).err(), Some(String::from(error)), "{}", fen);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+pub mod fen;
+pub mod pieces;
+pub mod position;
+pub mod bitboard;
+pub mod utils;
+pub mod evaluator;
+pub mod playground;
+pub mod board_navigator;
+pub mod zobrist;
+pub mod material;
+pub mod search;
+pub mod score;
+pub mod tuner;
+pub mod book;
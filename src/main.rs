@@ -1,38 +1,35 @@
-mod fen;
-mod pieces;
-mod position;
-mod bitboard;
-mod utils;
-mod evaluator;
-mod playground;
-mod board_navigator;
+mod analysis;
+mod api_error;
+mod cors;
 
 #[macro_use] extern crate rocket;
-use rocket::serde::{Serialize, Deserialize, json::Json};
-use rocket::http::Header;
-use rocket::{Request, Response};
-use rocket::fairing::{Fairing, Info, Kind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use crate::evaluator::evaluate;
+use rocket::serde::{Serialize, Deserialize, json::{self, Json}};
+use rocket::http::Status;
+use rocket::{Build, Rocket, State, Shutdown, Config};
+use rocket::figment::Figment;
+use rocket::response::stream::{Event, EventStream};
+use rocket::tokio::select;
+use rocket::data::ToByteUnit;
 
-pub struct CORS;
+use chess_engine::book::OpeningBook;
+use chess_engine::evaluator::{evaluate_with_trace, EvalParams, EvalTrace};
+use chess_engine::board_navigator::{get_legal_moves, is_in_check, make_move, parse_uci_move};
+use chess_engine::fen::try_from_fen;
+use chess_engine::playground;
+use chess_engine::position::Position;
+use chess_engine::score::Score;
+use chess_engine::search::{SearchLimits, SearchOptions, Searcher, TimeControl, TimeManager};
 
-#[rocket::async_trait]
-impl Fairing for CORS {
-    fn info(&self) -> Info {
-        Info {
-            name: "Add CORS headers to responses",
-            kind: Kind::Response
-        }
-    }
+use crate::analysis::{Analyses, AnalysisEvent};
+use crate::api_error::ApiError;
+use crate::cors::Cors;
 
-    async fn on_response<'r>(&self, _request: &'r Request<'_>, response: &mut Response<'r>) {
-        response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
-        response.set_header(Header::new("Access-Control-Allow-Methods", "POST, GET, PATCH, OPTIONS"));
-        response.set_header(Header::new("Access-Control-Allow-Headers", "*"));
-        response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
-    }
-}
+/// How long a search may take in milliseconds when the request doesn't give a clock or move time,
+/// so that a deep or unbounded search can't hold on to a worker
+const DEFAULT_MOVETIME: u64 = 10_000;
 
 #[get("/")]
 fn hello() -> &'static str {
@@ -49,25 +46,614 @@ fn play() -> &'static str {
 #[serde(crate = "rocket::serde")]
 struct RequestPayload<'r> {
     fen: &'r str,
+    /// played from the FEN before evaluating, in long algebraic notation
+    #[serde(default)]
+    moves: Vec<String>,
+    /// evaluate with these instead of the server's parameters, missing weights take their defaults
+    params: Option<EvalParams>,
+    /// search to this depth instead of evaluating statically
+    depth: Option<u8>,
+    /// search within this time budget instead of evaluating statically, searches without one are
+    /// stopped after `DEFAULT_MOVETIME`
+    time: Option<TimeControl>,
+    /// search this many nodes instead of evaluating statically
+    nodes: Option<u64>,
+    /// search for a mate in this many moves instead of evaluating statically
+    mate: Option<u8>,
+    /// only search these moves from the position, in long algebraic notation, for the default time when
+    /// there's no other limit
+    #[serde(default)]
+    searchmoves: Vec<String>,
+    /// how many of the best lines to return when searching, instead of the server's setting
+    multipv: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct ResponseLine {
+    evaluation: Score,
+    /// in long algebraic notation
+    pv: Vec<String>,
 }
 
 #[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
 struct ResponsePayload {
-    evaluation: f32,
+    /// from the side to move's point of view, `{"cp": 35}` or `{"mate": 0}` when it's checkmated
+    evaluation: Score,
+    /// contribution of each evaluation term, when asked for with `?trace=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<EvalTrace>,
+    /// best move in long algebraic notation and the depth completed, when searched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bestmove: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth: Option<u8>,
+    /// the best lines found, best first, when searched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines: Option<Vec<ResponseLine>>,
+    /// true when the best move came from the opening book rather than a search, the evaluation is then
+    /// the static one and there is no depth or lines
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    book: bool,
+}
+
+/// Validates the FEN and plays the moves from it, before any engine work is done
+fn build_position(fen: &str, moves: &[String]) -> Result<Position, ApiError> {
+    let mut position = try_from_fen(fen).map_err(ApiError::invalid_fen)?;
+
+    for (i, uci) in moves.iter().enumerate() {
+        let mv = parse_uci_move(&position, uci).map_err(|e| ApiError::invalid_move(i, e))?;
+        make_move(&mut position, &mv);
+    }
+
+    Ok(position)
+}
+
+/// The search limits, with the root moves checked against the position
+fn build_limits(position: &Position, depth: Option<u8>, nodes: Option<u64>, mate: Option<u8>, searchmoves: &[String]) -> Result<SearchLimits, ApiError> {
+    let searchmoves = searchmoves.iter().enumerate()
+        .map(|(i, uci)| parse_uci_move(position, uci).map_err(|e| ApiError::invalid_searchmove(i, e)))
+        .collect::<Result<_, _>>()?;
+
+    Ok(SearchLimits { depth, nodes, mate, searchmoves, ..SearchLimits::default() })
+}
+
+/// The server's search options with the request's number of lines, which has to be at least one
+fn search_options(options: &SearchOptions, multipv: Option<usize>) -> Result<SearchOptions, ApiError> {
+    match multipv {
+        Some(0) => Err(ApiError::invalid_multipv()),
+        multipv => Ok(SearchOptions { multi_pv: multipv.unwrap_or(options.multi_pv), ..options.clone() }),
+    }
+}
+
+/// The request's clock, or `DEFAULT_MOVETIME` when it doesn't limit the search
+fn search_time(time: Option<TimeControl>) -> TimeControl {
+    time.filter(TimeControl::is_limited)
+        .unwrap_or(TimeControl { movetime: Some(DEFAULT_MOVETIME), ..TimeControl::default() })
+}
+
+/// Stops the search when dropped, eg: when the request is dropped before the search finishes
+struct StopOnDrop(Arc<AtomicBool>);
+
+impl Drop for StopOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Statically evaluates the position, or searches it when a depth, time, node or mate limit is given,
+/// unless the opening book has a move for it
+#[post("/evaluate?<trace>", data = "<payload>")]
+async fn evaluate_post(
+    payload: Result<Json<RequestPayload<'_>>, json::Error<'_>>,
+    trace: Option<bool>,
+    params: &State<EvalParams>,
+    options: &State<SearchOptions>,
+    book: &State<OpeningBook>,
+    mut shutdown: Shutdown,
+) -> Result<Json<ResponsePayload>, ApiError> {
+    let payload = payload?;
+    let position = build_position(payload.fen, &payload.moves)?;
+    let params = payload.params.clone().unwrap_or_else(|| params.inner().clone());
+    let eval_trace = evaluate_with_trace(&position, &params);
+
+    let mut response = ResponsePayload {
+        evaluation: Score::relative(eval_trace.total, position.active_colour),
+        trace: trace.unwrap_or(false).then_some(eval_trace),
+        bestmove: None,
+        depth: None,
+        lines: None,
+        book: false,
+    };
+    // search moves alone are searched for the default time
+    let searching = payload.depth.is_some() || payload.time.is_some() || payload.nodes.is_some() || payload.mate.is_some()
+        || !payload.searchmoves.is_empty();
+
+    let limits = searching
+        .then(|| build_limits(&position, payload.depth, payload.nodes, payload.mate, &payload.searchmoves))
+        .transpose()?;
+
+    // a mate is always searched for, and a book move outside the search moves is passed over for a search
+    let book_move = limits.as_ref().filter(|_| payload.mate.is_none()).and_then(|limits| {
+        book.probe(&position).filter(|mv| limits.searchmoves.is_empty() || limits.searchmoves.contains(mv))
+    });
+
+    if get_legal_moves(&position).is_empty() {
+        response.evaluation = if is_in_check(&position) { Score::mated_in(0) } else { Score::DRAW };
+    } else if let Some(mv) = book_move {
+        response.bestmove = Some(mv.to_uci());
+        response.book = true;
+    } else if let Some(limits) = limits {
+        let time = search_time(payload.time.clone());
+        let options = search_options(options, payload.multipv)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let _stop_on_drop = StopOnDrop(stop.clone());
+        let search_stop = stop.clone();
+
+        // searching blocks, so it's kept off the async workers
+        let mut search = rocket::tokio::task::spawn_blocking(move || {
+            let time = TimeManager::new(&time, position.active_colour);
+            Searcher::new(search_stop, params)
+                .with_options(options)
+                .search(&position, &limits, time, |_| ())
+        });
+
+        // on shutdown the search is stopped and answers with the best move so far
+        let result = select! {
+            result = &mut search => result,
+            _ = &mut shutdown => {
+                stop.store(true, Ordering::Relaxed);
+                search.await
+            },
+        }.map_err(|e| ApiError::new(Status::InternalServerError, "search_failed", e.to_string()))?;
+
+        response.evaluation = result.score;
+        response.bestmove = result.best_move.map(|mv| mv.to_uci());
+        response.depth = Some(result.depth);
+        response.lines = Some(result.lines.into_iter().map(|line| ResponseLine {
+            evaluation: line.score,
+            pv: line.pv.iter().map(|mv| mv.to_uci()).collect(),
+        }).collect());
+    }
+
+    Ok(Json(response))
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct AnalysisStarted {
+    id: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+struct AnalysisBestMove {
+    bestmove: Option<String>,
 }
 
-#[post("/evaluate", data = "<fen>")]
-fn evaluate_post(fen: Json<RequestPayload>) -> Json<ResponsePayload> {
-    Json(ResponsePayload {
-        evaluation: evaluate(fen.fen, 1)
+/// Streams the progress of a search as server-sent events: `start` with the id to cancel it by,
+/// `info` for each of the best lines after every completed depth and `bestmove` when the search ends
+#[get("/analyse?<fen>&<moves>&<depth>&<nodes>&<mate>&<searchmoves>&<multipv>&<time..>")]
+#[allow(clippy::too_many_arguments)]
+fn analyse(
+    fen: &str,
+    moves: Vec<String>,
+    depth: Option<u8>,
+    nodes: Option<u64>,
+    mate: Option<u8>,
+    searchmoves: Vec<String>,
+    multipv: Option<usize>,
+    time: TimeControl,
+    analyses: &State<Analyses>,
+    params: &State<EvalParams>,
+    options: &State<SearchOptions>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], ApiError> {
+    let position = build_position(fen, &moves)?;
+    let limits = build_limits(&position, depth, nodes, mate, &searchmoves)?;
+    let options = search_options(options, multipv)?;
+
+    let (guard, mut events) = analyses.start(position, limits, time, params.inner().clone(), options);
+
+    Ok(EventStream! {
+        // the search is stopped when the stream is dropped
+        let guard = guard;
+        yield Event::json(&AnalysisStarted { id: guard.id }).event("start");
+
+        loop {
+            let event = select! {
+                event = events.recv() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                _ = &mut shutdown => break,
+            };
+
+            yield match event {
+                AnalysisEvent::Info(info) => Event::json(&info).event("info"),
+                AnalysisEvent::BestMove(bestmove) => Event::json(&AnalysisBestMove { bestmove }).event("bestmove"),
+            };
+        }
     })
 }
 
-#[options("/evaluate")]
-fn evaluate_options() { }
+#[delete("/analyse/<id>")]
+fn analyse_stop(id: u64, analyses: &State<Analyses>) -> Status {
+    if analyses.stop(id) { Status::NoContent } else { Status::NotFound }
+}
 
 #[launch]
 fn rocket() -> _ {
-    rocket::build().attach(CORS).mount("/", routes![hello, play, evaluate_post, evaluate_options])
+    build_rocket(Config::figment())
+}
+
+fn build_rocket(figment: Figment) -> Rocket<Build> {
+    // small default request size limit, unless it's been configured in Rocket.toml or the environment
+    let figment = figment.join(("limits.json", 16.kibibytes()));
+
+    rocket::custom(figment)
+        .attach(Cors)
+        .attach(EvalParams::fairing())
+        .attach(SearchOptions::fairing())
+        .attach(OpeningBook::fairing())
+        .manage(Analyses::default())
+        .mount("/", routes![hello, play, evaluate_post, analyse, analyse_stop])
+        .register("/", catchers![
+            api_error::bad_request,
+            api_error::not_found,
+            api_error::payload_too_large,
+            api_error::unprocessable_entity,
+            api_error::internal_error,
+            api_error::default_catcher,
+        ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_rocket, rocket, search_time, DEFAULT_MOVETIME};
+    use chess_engine::search::TimeControl;
+    use chess_engine::book::polyglot_key;
+    use chess_engine::fen::from_fen;
+    use rocket::error::ErrorKind;
+    use rocket::Config;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::Value;
+
+    #[test]
+    fn analyse_streams_info_until_bestmove() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.get("/analyse?fen=6k1/5ppp/8/8/8/8/8/R5K1%20w%20-%20-&depth=3").dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().unwrap();
+        assert!(body.starts_with("event:start\ndata:{\"id\":0}"));
+        assert!(body.contains("event:info\ndata:{\"depth\":1,"));
+        assert!(body.contains("event:bestmove\ndata:{\"bestmove\":\"a1a8\"}"));
+    }
+
+    #[test]
+    fn analyse_rejects_invalid_fen() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.get("/analyse?fen=8/8/8/8/8/8/8/8%20w%20-%20-").dispatch();
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(
+            response.into_string().unwrap(),
+            r#"{"error":{"code":"invalid_fen","message":"Each side must have exactly one king","field":"fen"}}"#
+        );
+    }
+
+    #[test]
+    fn evaluate_plays_moves_from_fen() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -", "moves": ["e2e4", "e7e5"]}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn evaluate_traces_terms_on_request() {
+        let client = Client::tracked(rocket()).unwrap();
+        let body = r#"{"fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -"}"#;
+
+        let plain = client.post("/evaluate").header(ContentType::JSON).body(body).dispatch();
+        assert_eq!(plain.into_string().unwrap(), r#"{"evaluation":{"cp":0}}"#);
+
+        let traced = client.post("/evaluate?trace=true").header(ContentType::JSON).body(body).dispatch();
+        let json = traced.into_json::<Value>().unwrap();
+        assert_eq!(json["trace"]["phase"], 24);
+        assert_eq!(json["trace"]["material"]["white"], json["trace"]["material"]["black"]);
+        assert_eq!(json["trace"]["king_safety"]["white"]["mg"], 36);
+    }
+
+    #[test]
+    fn evaluate_with_params_from_request() {
+        let client = Client::tracked(rocket()).unwrap();
+        let evaluate = |body: &str| {
+            let response = client.post("/evaluate").header(ContentType::JSON).body(body).dispatch();
+            response.into_json::<Value>().unwrap()["evaluation"]["cp"].as_i64().unwrap()
+        };
+
+        let default = evaluate(r#"{"fen": "4k3/p7/8/8/8/8/P2Q4/4K3 w - -"}"#);
+        let worthless_queen = evaluate(
+            r#"{"fen": "4k3/p7/8/8/8/8/P2Q4/4K3 w - -", "params": {"material": {"values": {"queen": {"mg": 0, "eg": 0}}}}}"#
+        );
+
+        assert!(default > 900);
+        assert!(worthless_queen < 100);
+    }
+
+    #[test]
+    fn evaluate_searches_within_the_time_budget() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "4k3/8/8/3q4/8/8/3R4/3K4 w - -", "time": {"movetime": 300}}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let body: Value = response.into_json().unwrap();
+        assert_eq!(body["bestmove"], "d2d5");
+        assert!(body["depth"].as_u64().unwrap() >= 1);
+    }
+
+    #[test]
+    fn search_without_a_clock_gets_the_default_time() {
+        assert_eq!(search_time(None).movetime, Some(DEFAULT_MOVETIME));
+        assert_eq!(search_time(Some(TimeControl { winc: Some(100), ..TimeControl::default() })).movetime, Some(DEFAULT_MOVETIME));
+
+        let clock = TimeControl { wtime: Some(5000), ..TimeControl::default() };
+        assert_eq!(search_time(Some(clock.clone())), clock);
+    }
+
+    #[test]
+    fn evaluate_searches_with_the_configured_threads() {
+        let figment = Config::figment().merge(("search.threads", 3));
+        let client = Client::tracked(build_rocket(figment)).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "6k1/5ppp/8/8/8/8/8/R5K1 w - -", "depth": 4}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let body: Value = response.into_json().unwrap();
+        assert_eq!(body["bestmove"], "a1a8");
+        assert_eq!(body["evaluation"]["mate"], 1);
+    }
+
+    #[test]
+    fn zero_threads_fails_to_launch() {
+        let figment = Config::figment().merge(("search.threads", 0));
+
+        match Client::tracked(build_rocket(figment)) {
+            Err(error) => assert!(matches!(error.kind(), ErrorKind::FailedFairings(_))),
+            Ok(_) => panic!("launched without any search threads"),
+        }
+    }
+
+    #[test]
+    fn evaluate_returns_the_best_lines() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "4k3/8/8/3q4/8/8/3R4/3K4 w - -", "depth": 3, "multipv": 3}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let body: Value = response.into_json().unwrap();
+        let lines = body["lines"].as_array().unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["pv"][0], "d2d5");
+        assert_eq!(lines[0]["evaluation"], body["evaluation"]);
+        assert!(lines[1]["evaluation"]["cp"].as_i64() <= lines[0]["evaluation"]["cp"].as_i64());
+    }
+
+    #[test]
+    fn analyse_streams_each_line() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.get("/analyse?fen=4k3/8/8/3q4/8/8/3R4/3K4%20w%20-%20-&depth=2&multipv=2").dispatch();
+
+        let body = response.into_string().unwrap();
+        assert!(body.contains("\"multipv\":1}"));
+        assert!(body.contains("\"multipv\":2}"));
+        assert!(!body.contains("\"multipv\":3}"));
+    }
+
+    #[test]
+    fn analyse_accepts_a_clock() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.get("/analyse?fen=4k3/8/8/3q4/8/8/3R4/3K4%20w%20-%20-&wtime=2000&winc=0").dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.into_string().unwrap().contains("event:bestmove\ndata:{\"bestmove\":\"d2d5\"}"));
+    }
+
+    #[test]
+    fn evaluate_plays_from_the_book() {
+        // a book of 1. e4 alone, e2 to e4 counting squares from a1
+        let start = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let mut entry = polyglot_key(&start).to_be_bytes().to_vec();
+        entry.extend((28u16 | 12 << 6).to_be_bytes());
+        entry.extend([0, 1, 0, 0, 0, 0]);
+
+        let path = std::env::temp_dir().join(format!("chess-engine-book-{}.bin", std::process::id()));
+        std::fs::write(&path, entry).unwrap();
+        let figment = Config::figment().merge(("book.file", path.display().to_string()));
+        let client = Client::tracked(build_rocket(figment)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let search = |body: &str| client.post("/evaluate").header(ContentType::JSON).body(body).dispatch().into_json::<Value>().unwrap();
+        let in_book = search(r#"{"fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "depth": 2}"#);
+        assert_eq!((&in_book["bestmove"], &in_book["book"]), (&Value::from("e2e4"), &Value::from(true)));
+
+        let illegal_searchmove = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "depth": 2, "searchmoves": ["e2e4", "e2e5"]}"#)
+            .dispatch();
+        assert_eq!(illegal_searchmove.status(), Status::UnprocessableEntity);
+
+        let out_of_book = search(r#"{"fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "moves": ["d2d4"], "depth": 2}"#);
+        assert!(out_of_book.get("book").is_none());
+        assert!(out_of_book["depth"].as_u64().is_some());
+    }
+
+    #[test]
+    fn missing_book_file_fails_to_launch() {
+        let figment = Config::figment().merge(("book.file", "/nowhere/book.bin"));
+
+        match Client::tracked(build_rocket(figment)) {
+            Err(error) => assert!(matches!(error.kind(), ErrorKind::FailedFairings(_))),
+            Ok(_) => panic!("launched without the opening book"),
+        }
+    }
+
+    #[test]
+    fn missing_params_file_fails_to_launch() {
+        let figment = Config::figment().merge(("eval_params", "/nowhere/params.json"));
+
+        match Client::tracked(build_rocket(figment)) {
+            Err(error) => assert!(matches!(error.kind(), ErrorKind::FailedFairings(_))),
+            Ok(_) => panic!("launched without the evaluation parameters"),
+        }
+    }
+
+    #[test]
+    fn evaluate_reports_checkmate_as_mate() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq -"}"#)
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), r#"{"evaluation":{"mate":0}}"#);
+    }
+
+    #[test]
+    fn evaluate_rejects_illegal_move() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -", "moves": ["e2e4", "e2e4"]}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(
+            response.into_string().unwrap(),
+            r#"{"error":{"code":"invalid_move","message":"Illegal move 'e2e4'","field":"moves[1]"}}"#
+        );
+    }
+
+    #[test]
+    fn evaluate_rejects_illegal_searchmove() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "4k3/8/8/3q4/8/8/3R4/3K4 w - -", "depth": 2, "searchmoves": ["d2d5", "d2e5"]}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(
+            response.into_string().unwrap(),
+            r#"{"error":{"code":"invalid_move","message":"Illegal move 'd2e5'","field":"searchmoves[1]"}}"#
+        );
+    }
+
+    #[test]
+    fn evaluate_rejects_zero_lines() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "4k3/8/8/3q4/8/8/3R4/3K4 w - -", "depth": 2, "multipv": 0}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(
+            response.into_string().unwrap(),
+            r#"{"error":{"code":"invalid_payload","message":"multipv must be at least 1","field":"multipv"}}"#
+        );
+
+        let response = client.get("/analyse?fen=4k3/8/8/3q4/8/8/3R4/3K4%20w%20-%20-&depth=2&multipv=0").dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn evaluate_searches_the_searchmoves_without_other_limits() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "6k1/5ppp/8/8/8/8/8/R5K1 w - -", "searchmoves": ["a1a8"]}"#)
+            .dispatch();
+
+        let body: Value = response.into_json().unwrap();
+        assert_eq!(body["bestmove"], "a1a8");
+        assert_eq!(body["evaluation"]["mate"], 1);
+    }
+
+    #[test]
+    fn evaluate_finds_a_mate_in_two() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate")
+            .header(ContentType::JSON)
+            .body(r#"{"fen": "k7/8/2K5/8/8/8/8/7R w - -", "mate": 2}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let body: Value = response.into_json().unwrap();
+        assert_eq!(body["evaluation"]["mate"], 2);
+        assert_eq!(body["bestmove"], "c6b6");
+    }
+
+    #[test]
+    fn evaluate_rejects_malformed_json() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate").header(ContentType::JSON).body(r#"{"fen": "#).dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+        assert!(response.into_string().unwrap().starts_with(r#"{"error":{"code":"malformed_json","#));
+    }
+
+    #[test]
+    fn evaluate_rejects_payload_of_wrong_shape() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.post("/evaluate").header(ContentType::JSON).body(r#"{"fen": 42}"#).dispatch();
+
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert!(response.into_string().unwrap().starts_with(r#"{"error":{"code":"invalid_payload","#));
+    }
+
+    #[test]
+    fn evaluate_rejects_large_payload() {
+        let client = Client::tracked(rocket()).unwrap();
+        let body = format!(r#"{{"fen": "{}"}}"#, "8".repeat(32 * 1024));
+        let response = client.post("/evaluate").header(ContentType::JSON).body(body).dispatch();
+
+        assert_eq!(response.status(), Status::PayloadTooLarge);
+        assert!(response.into_string().unwrap().starts_with(r#"{"error":{"code":"payload_too_large","#));
+    }
+
+    #[test]
+    fn unknown_route_is_json_not_found() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.get("/nowhere").dispatch();
+
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(
+            response.into_string().unwrap(),
+            r#"{"error":{"code":"not_found","message":"No route for GET /nowhere","field":null}}"#
+        );
+    }
+
+    #[test]
+    fn stopping_unknown_analysis_is_not_found() {
+        let client = Client::tracked(rocket()).unwrap();
+        let response = client.delete("/analyse/42").dispatch();
+
+        assert_eq!(response.status(), Status::NotFound);
+    }
 }
@@ -0,0 +1,135 @@
+use crate::bitboard::BitBoard;
+use crate::pieces::{Pieces, Side};
+
+/// Bits for the count of one kind of piece, enough for every pawn promoting to the same piece
+const COUNT_BITS: u32 = 4;
+const COUNT_MASK: u64 = (1 << COUNT_BITS) - 1;
+
+/**
+ * How many of each piece each side has, packed 4 bits a count, so it doubles as the key of the material
+ *
+ * Kings aren't counted, there's always one of each. Kept up to date by `Position` as pieces are placed and
+ * removed, rather than counted again from the bitboards
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MaterialKey(pub u64);
+
+impl MaterialKey {
+    /// Counts the pieces on the boards, indexed as `Position::sides` and `Position::pieces`
+    pub fn from_boards(sides: &[BitBoard; 2], pieces: &[BitBoard; 6]) -> MaterialKey {
+        let mut key = MaterialKey::default();
+
+        for side in [Side::White, Side::Black] {
+            for (piece, piece_bb) in pieces[..Pieces::KING].iter().enumerate() {
+                let count = (sides[side_index(side)] & *piece_bb).count() as u64;
+                debug_assert!(count <= COUNT_MASK, "{} pieces overflow the material key", count);
+                key.0 += count << shift(side, piece);
+            }
+        }
+
+        key
+    }
+
+    pub fn count(self, side: Side, piece: usize) -> u32 {
+        if piece == Pieces::KING {
+            return 1;
+        }
+
+        ((self.0 >> shift(side, piece)) & COUNT_MASK) as u32
+    }
+
+    pub fn add(&mut self, side: Side, piece: usize) {
+        if piece != Pieces::KING {
+            debug_assert!(self.count(side, piece) < COUNT_MASK as u32, "piece count overflows the material key");
+            self.0 += 1 << shift(side, piece);
+        }
+    }
+
+    pub fn remove(&mut self, side: Side, piece: usize) {
+        if piece != Pieces::KING {
+            self.0 -= 1 << shift(side, piece);
+        }
+    }
+
+    /// The side has nothing but its king
+    pub fn is_bare_king(self, side: Side) -> bool {
+        (Pieces::PAWN..Pieces::KING).all(|piece| self.count(side, piece) == 0)
+    }
+
+    /// The same material with the colours swapped
+    pub fn mirrored(self) -> MaterialKey {
+        let side_bits = COUNT_BITS * Pieces::KING as u32;
+        let white = self.0 & ((1 << side_bits) - 1);
+
+        MaterialKey(self.0 >> side_bits | white << side_bits)
+    }
+}
+
+fn side_index(side: Side) -> usize {
+    match side {
+        Side::White => 0,
+        Side::Black => 1,
+    }
+}
+
+/// White's counts in the low bits, then black's, each in the order of `Pieces`
+fn shift(side: Side, piece: usize) -> u32 {
+    (side_index(side) * Pieces::KING + piece) as u32 * COUNT_BITS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MaterialKey;
+    use crate::board_navigator::{get_legal_moves, make_move, parse_uci_move};
+    use crate::fen::from_fen;
+    use crate::pieces::{Pieces, Side};
+
+    #[test]
+    fn counts_the_pieces_of_each_side() {
+        let key = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R2QKBNR w KQkq -").material_key;
+
+        assert_eq!(key.count(Side::White, Pieces::PAWN), 8);
+        assert_eq!(key.count(Side::White, Pieces::KNIGHT), 1);
+        assert_eq!(key.count(Side::White, Pieces::BISHOP), 1);
+        assert_eq!(key.count(Side::Black, Pieces::KNIGHT), 2);
+        assert_eq!(key.count(Side::Black, Pieces::KING), 1);
+        assert!(from_fen("4k3/8/8/8/8/8/4P3/4K3 w - -").material_key.is_bare_king(Side::Black));
+    }
+
+    #[test]
+    fn moves_keep_the_key_up_to_date() {
+        // captures, en passant, castling and promotions with and without a capture
+        let fens = [
+            "r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/3PP3/5N2/PPP2PPP/RNBQKB1R b KQkq -",
+            "1r2k3/2P5/8/8/8/8/8/4K3 w - -",
+        ];
+
+        for fen in fens {
+            let position = from_fen(fen);
+
+            for mv in get_legal_moves(&position) {
+                let mut played = position.clone();
+                make_move(&mut played, &mv);
+
+                assert_eq!(played.material_key, MaterialKey::from_boards(&played.sides, &played.pieces), "{} {}", fen, mv.to_uci());
+            }
+        }
+
+        let mut position = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+        for uci in ["e2e4", "d7d5", "e4d5", "d8d5"] {
+            let mv = parse_uci_move(&position, uci).unwrap();
+            make_move(&mut position, &mv);
+        }
+        assert_eq!((position.material_key.count(Side::White, Pieces::PAWN), position.material_key.count(Side::Black, Pieces::PAWN)), (7, 7));
+    }
+
+    #[test]
+    fn mirroring_swaps_the_sides() {
+        let position = from_fen("4k3/pp6/8/8/8/8/3Q4/2B1K3 w - -");
+        let mirrored = position.mirrored().material_key;
+
+        assert_eq!(mirrored, position.material_key.mirrored());
+        assert_eq!((mirrored.count(Side::Black, Pieces::QUEEN), mirrored.count(Side::White, Pieces::PAWN)), (1, 2));
+    }
+}
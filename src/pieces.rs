@@ -1,10 +1,19 @@
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Side {
     White,
     Black,
 }
 
+impl Side {
+    pub fn opponent(&self) -> Side {
+        match self {
+            Side::White => Side::Black,
+            Side::Black => Side::White,
+        }
+    }
+}
+
 pub struct Pieces;
 impl Pieces {
     pub const PAWN: usize = 0;
@@ -1,7 +1,7 @@
 use crate::fen::{to_fen, from_fen, fen_to_asci_board};
 use crate::pieces::{Pieces, PieceAsci};
 use crate::bitboard::{BitBoard, to_asci_board, asci_board_indicies};
-use crate::evaluator::evaluate;
+use crate::evaluator::{evaluate_position, EvalParams};
 
 const HEADER: &str =       "64↓             48↓             32↓             16↓             1↓";
 const RANKS_HEADER: &str = " 8↓      7↓      6↓      5↓      4↓      3↓      2↓      1↓       ";
@@ -45,7 +45,7 @@ pub fn play() {
     println!("\nPawn ASCI Board:\n{}", bb_asci);
     println!("\nNumbered ASCI Board:\n{}", asci_board_indicies());
 
-    println!("Evaluation: {}", evaluate(starting_fen, 1));
+    println!("Evaluation: {}", evaluate_position(&position, &EvalParams::default()));
 
     let built_fen = to_fen(position.clone());
     println!("\nFEN built from position:\n\t{}", built_fen);
@@ -2,9 +2,10 @@
 
 use crate::bitboard::BitBoard;
 use crate::fen::{to_fen, fen_to_asci_board};
+use crate::material::MaterialKey;
 use crate::utils::{coord_from_index};
-use crate::board_navigator::{Coord, Piece, CastlingSide};
-use crate::pieces::Side;
+use crate::board_navigator::{attackers_to, bishop_attacks, rook_attacks, Coord, Piece, CastlingSide};
+use crate::pieces::{Pieces, Side};
 
 use std::fmt;
 
@@ -24,6 +25,9 @@ pub struct Position {
     pub half_move_clock: usize,
 
     pub full_moves: usize,
+
+    /// pieces of each side, kept up to date by `place_piece` and `remove_piece`
+    pub material_key: MaterialKey,
 }
 
 const SIDE_WHITE_INDEX: usize = 0;
@@ -46,6 +50,7 @@ impl Position {
         full_moves: usize,
     ) -> Position {
         Position {
+            material_key: MaterialKey::from_boards(&stateless_position.sides, &stateless_position.pieces),
             sides: stateless_position.sides,
             pieces: stateless_position.pieces,
             active_colour,
@@ -217,6 +222,97 @@ impl Position {
         self.get_black_pieces() & self.get_kings()
     }
 
+    pub fn get_side_pieces(&self, side: Side) -> BitBoard {
+        match side {
+            Side::White => self.get_white_pieces(),
+            Side::Black => self.get_black_pieces(),
+        }
+    }
+
+    /// `piece` is one of the `Pieces` indexes
+    pub fn get_coloured_pieces(&self, side: Side, piece: usize) -> BitBoard {
+        self.get_side_pieces(side) & self.pieces[piece]
+    }
+
+    pub fn get_occupied(&self) -> BitBoard {
+        self.get_white_pieces() | self.get_black_pieces()
+    }
+
+    fn get_king_square(&self, side: Side) -> Option<usize> {
+        let kings = self.get_coloured_pieces(side, Pieces::KING).0;
+        if kings == 0 { None } else { Some(kings.trailing_zeros() as usize) }
+    }
+
+    /// Opponent pieces giving check to the king of the active colour
+    pub fn checkers(&self) -> BitBoard {
+        match self.get_king_square(self.active_colour) {
+            Some(king) => attackers_to(self, king, self.active_colour.opponent(), self.get_occupied()),
+            None => BitBoard(0),
+        }
+    }
+
+    /// Pieces of the active colour that can't move off the line between their king and an opponent slider
+    pub fn pinned(&self) -> BitBoard {
+        self.pins(self.active_colour).0
+    }
+
+    /// The side's pieces pinned to its king, and the opponent sliders pinning them
+    pub fn pins(&self, side: Side) -> (BitBoard, BitBoard) {
+        let Some(king) = self.get_king_square(side) else {
+            return (BitBoard(0), BitBoard(0));
+        };
+
+        let opponent = side.opponent();
+        let occupied = self.get_occupied().0;
+        let own = self.get_side_pieces(side).0;
+        let straight = (self.get_coloured_pieces(opponent, Pieces::ROOK) | self.get_coloured_pieces(opponent, Pieces::QUEEN)).0;
+        let diagonal = (self.get_coloured_pieces(opponent, Pieces::BISHOP) | self.get_coloured_pieces(opponent, Pieces::QUEEN)).0;
+
+        // sliders that would attack the king on an empty board
+        let snipers = [
+            (rook_attacks(king, 0) & straight, rook_attacks as fn(usize, u64) -> u64),
+            (bishop_attacks(king, 0) & diagonal, bishop_attacks),
+        ];
+
+        let mut pinned = 0;
+        let mut pinners = 0;
+        for (mut sliders, attacks) in snipers {
+            while sliders != 0 {
+                let slider = sliders.trailing_zeros() as usize;
+                sliders &= sliders - 1;
+
+                // squares strictly between the king and the slider
+                let between = attacks(king, 1 << slider) & attacks(slider, 1 << king) & occupied;
+                if between.count_ones() == 1 && between & own != 0 {
+                    pinned |= between;
+                    pinners |= 1 << slider;
+                }
+            }
+        }
+
+        (BitBoard(pinned), BitBoard(pinners))
+    }
+
+    /// The same position with the board flipped vertically and the colours swapped,
+    /// so white's advantage becomes black's
+    pub fn mirrored(&self) -> Position {
+        Position {
+            sides: [self.sides[SIDE_BLACK_INDEX].flip_vertical(), self.sides[SIDE_WHITE_INDEX].flip_vertical()],
+            pieces: self.pieces.map(BitBoard::flip_vertical),
+            active_colour: self.active_colour.opponent(),
+            castling: Castling {
+                K: self.castling.k,
+                Q: self.castling.q,
+                k: self.castling.K,
+                q: self.castling.Q,
+            },
+            en_passant_target: self.en_passant_target.as_ref().map(|target| Coord(target.0, 9 - target.1)),
+            half_move_clock: self.half_move_clock,
+            full_moves: self.full_moves,
+            material_key: self.material_key.mirrored(),
+        }
+    }
+
     pub fn make_move(&mut self, from: &Coord, to: &Coord, castling: &Option<CastlingSide>) -> Result<i32, String> {
         let from_index = from.to_index();
         let to_index = to.to_index();
@@ -254,6 +350,11 @@ impl Position {
 
     pub fn remove_piece(&mut self, index: i32) {
         if self.has_piece(index) {
+            let side = if self.is_white(index) { Side::White } else { Side::Black };
+            if let Some(piece) = self.get_piece(index) {
+                self.material_key.remove(side, piece_index(piece));
+            }
+
             let side_bb = self.get_side_bitboard(index);
             side_bb.unset_index(index as u8);
             let piece_bb_opt = self.get_piece_bitboard(index);
@@ -264,17 +365,18 @@ impl Position {
     }
 
     /// removes the pawn ahead of the en_passant_targer, that had moved by 2 squares
-    /// the pawn will either be north or south of the en_passant_target
+    /// a target on the 3rd rank has a white pawn north of it, on the 6th rank a black pawn south of it
     pub fn remove_pawn_by_en_passant(&mut self) {
         if let Some(en_passant_target) = &self.en_passant_target {
-            if let Some(north) = en_passant_target.n(1, 1) {
-                if self.has_piece(north.to_index()) {
-                    self.remove_piece(north.to_index());
-                    self.en_passant_target = None;
-                } else if let Some(south) = en_passant_target.s(1, 1) {
-                    self.remove_piece(south.to_index());
-                    self.en_passant_target = None;
-                }
+            let pawn_coord = if en_passant_target.1 == 3 {
+                en_passant_target.n(1, 1)
+            } else {
+                en_passant_target.s(1, 1)
+            };
+
+            if let Some(pawn_coord) = pawn_coord {
+                self.remove_piece(pawn_coord.to_index());
+                self.en_passant_target = None;
             }
         }
     }
@@ -290,7 +392,7 @@ impl Position {
         println!("En Passant: {:?}", self.en_passant_target);
     }
 
-    fn place_piece(&mut self, index: i32, side: Side, piece: Piece) {
+    pub fn place_piece(&mut self, index: i32, side: Side, piece: Piece) {
         if self.has_piece(index) {
             // if there is a piece, remove it
             self.remove_piece(index);
@@ -313,6 +415,8 @@ impl Position {
         };
 
         piece_bitboard.set_index(index as u8);
+
+        self.material_key.add(side, piece_index(piece));
     }
 
     fn castle(&mut self, castling: &Option<CastlingSide>) {
@@ -361,7 +465,7 @@ impl Position {
         None
     }
 
-    fn get_piece(&self, index: i32) -> Option<Piece> {
+    pub fn get_piece(&self, index: i32) -> Option<Piece> {
         if self.is_pawn(index) {
             return Some(Piece::P);
         } else if self.is_bishop(index) {
@@ -400,6 +504,18 @@ impl Position {
     }
 }
 
+/// Index of the piece in `Position::pieces`
+fn piece_index(piece: Piece) -> usize {
+    match piece {
+        Piece::P => PIECE_PAWN_INDEX,
+        Piece::B => PIECE_BISHOP_INDEX,
+        Piece::N => PIECE_KNIGHT_INDEX,
+        Piece::R => PIECE_ROOK_INDEX,
+        Piece::Q => PIECE_QUEEN_INDEX,
+        Piece::K => PIECE_KING_INDEX,
+    }
+}
+
 pub struct StatelessPosition {
     /// Board for each side
     sides: [BitBoard; 2],
@@ -466,7 +582,7 @@ impl fmt::Debug for Castling {
 #[cfg(test)]
 mod tests {
     use super::{Side, Piece, Coord};
-    use crate::{board_navigator::CastlingSide, fen::{fen_to_asci_board, from_fen}};
+    use crate::{board_navigator::{get_legal_moves, CastlingSide}, fen::{fen_to_asci_board, from_fen, to_fen}};
 
     #[test]
     fn remove_piece() {
@@ -646,5 +762,53 @@ mod tests {
 
         assert!(position.en_passant_target.is_none());
     }
-}
 
+    #[test]
+    fn mirrored_swaps_colours() {
+        let position = from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K1R1 w Qkq -").mirrored();
+
+        assert_eq!(to_fen(position.clone()), to_fen(from_fen("r3k1r1/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQq -")));
+        assert_eq!(position.active_colour, Side::Black);
+        assert_eq!(format!("{:?}", position.castling), "KQq");
+    }
+
+    #[test]
+    fn mirrored_flips_en_passant_target() {
+        let position = from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6").mirrored();
+
+        assert_eq!(position.en_passant_target.as_ref().map(|target| target.to_string()), Some(String::from("f3")));
+        assert_eq!(to_fen(position.mirrored()), to_fen(from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6")));
+    }
+
+    #[test]
+    fn mirrored_has_the_same_moves() {
+        let position = from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
+
+        assert_eq!(get_legal_moves(&position).len(), get_legal_moves(&position.mirrored()).len());
+    }
+
+    #[test]
+    fn checkers_finds_every_checking_piece() {
+        let position = from_fen("4k3/8/8/1B6/8/8/8/4R1K1 b - -");
+        let checkers = position.checkers().get_indicies();
+
+        assert_eq!(checkers, vec![Coord('b', 5).to_index() as u32, Coord('e', 1).to_index() as u32]);
+        assert_eq!(from_fen("4k3/8/8/8/8/8/8/R5K1 b - -").checkers().count(), 0);
+    }
+
+    #[test]
+    fn pinned_pieces_are_on_the_line_to_the_king() {
+        // the knight on e4 is pinned by the rook and the bishop on d2 by the bishop, the pawn on c2 is free
+        let position = from_fen("4r1k1/8/8/b7/4N3/8/2PB4/4K3 w - -");
+        let pinned = position.pinned().get_indicies();
+
+        assert_eq!(pinned, vec![Coord('e', 4).to_index() as u32, Coord('d', 2).to_index() as u32]);
+    }
+
+    #[test]
+    fn two_pieces_in_the_way_are_not_pinned() {
+        let position = from_fen("4r1k1/8/8/8/4N3/8/4B3/4K3 w - -");
+
+        assert_eq!(position.pinned().count(), 0);
+    }
+}
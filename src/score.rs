@@ -0,0 +1,166 @@
+use std::fmt;
+use std::ops::Neg;
+
+use rocket::serde::{Serialize, Serializer};
+
+use crate::pieces::Side;
+
+/// Deepest a mate can be found at, in plies from the root
+const MAX_MATE_PLY: i32 = 128;
+
+/**
+ * A score from the point of view of the side to move, in centipawns or, past `MATE_BOUND`,
+ * a forced mate with the distance to it in plies taken off `MATE`
+ *
+ * Negating it gives the score from the opponent's point of view, as negamax expects
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Score(pub i32);
+
+impl Score {
+    pub const DRAW: Score = Score(0);
+    pub const MATE: Score = Score(32_000);
+    /// above any score a search can return, for the initial window
+    pub const INFINITY: Score = Score(32_001);
+    /// scores at least this far from zero are mates
+    pub const MATE_BOUND: Score = Score(32_000 - MAX_MATE_PLY);
+
+    pub const fn cp(centipawns: i32) -> Score {
+        Score(centipawns)
+    }
+
+    /// A centipawn score that favours white when positive, seen from the side
+    pub fn relative(centipawns: i32, side: Side) -> Score {
+        match side {
+            Side::White => Score::cp(centipawns),
+            Side::Black => Score::cp(-centipawns),
+        }
+    }
+
+    /// The side to move gets mated in `ply` plies
+    pub fn mated_in(ply: u8) -> Score {
+        Score(-Score::MATE.0 + ply as i32)
+    }
+
+    pub fn is_mate(self) -> bool {
+        self.0.abs() >= Score::MATE_BOUND.0
+    }
+
+    /// Full moves to mate, negative when the side to move is getting mated
+    pub fn mate_moves(self) -> Option<i32> {
+        if self >= Score::MATE_BOUND {
+            Some((Score::MATE.0 - self.0 + 1) / 2)
+        } else if self <= -Score::MATE_BOUND {
+            Some(-(Score::MATE.0 + self.0) / 2)
+        } else {
+            None
+        }
+    }
+
+    /// Mate scores are stored relative to the node so they stay correct when reached from a different ply
+    pub fn to_tt(self, ply: u8) -> Score {
+        if self >= Score::MATE_BOUND {
+            Score(self.0 + ply as i32)
+        } else if self <= -Score::MATE_BOUND {
+            Score(self.0 - ply as i32)
+        } else {
+            self
+        }
+    }
+
+    pub fn from_tt(stored: Score, ply: u8) -> Score {
+        if stored >= Score::MATE_BOUND {
+            Score(stored.0 - ply as i32)
+        } else if stored <= -Score::MATE_BOUND {
+            Score(stored.0 + ply as i32)
+        } else {
+            stored
+        }
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score(-self.0)
+    }
+}
+
+impl fmt::Display for Score {
+    /// As UCI reports it, eg: `cp 35` or `mate -3`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mate_moves() {
+            Some(moves) => write!(f, "mate {}", moves),
+            None => write!(f, "cp {}", self.0),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "lowercase")]
+enum ScoreJson {
+    Cp(i32),
+    Mate(i32),
+}
+
+/// `{"cp": 35}` or `{"mate": -3}`
+impl Serialize for Score {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.mate_moves() {
+            Some(moves) => ScoreJson::Mate(moves),
+            None => ScoreJson::Cp(self.0),
+        }.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Score;
+    use crate::pieces::Side;
+    use rocket::serde::json::to_string;
+
+    fn mate_in(ply: u8) -> Score {
+        -Score::mated_in(ply)
+    }
+
+    #[test]
+    fn mate_distance_in_moves() {
+        assert_eq!(mate_in(1).mate_moves(), Some(1));
+        assert_eq!(mate_in(5).mate_moves(), Some(3));
+        assert_eq!(Score::mated_in(0).mate_moves(), Some(0));
+        assert_eq!(Score::mated_in(6).mate_moves(), Some(-3));
+        assert_eq!(Score::cp(350).mate_moves(), None);
+    }
+
+    #[test]
+    fn negamax_ordering() {
+        assert!(mate_in(1) > mate_in(3));
+        assert!(Score::mated_in(2) < Score::mated_in(4));
+        assert!(Score::mated_in(4) < Score::cp(-5000));
+        assert_eq!(-mate_in(3), Score::mated_in(3));
+    }
+
+    #[test]
+    fn relative_to_side_to_move() {
+        assert_eq!(Score::relative(35, Side::White), Score::cp(35));
+        assert_eq!(Score::relative(35, Side::Black), Score::cp(-35));
+    }
+
+    #[test]
+    fn tt_round_trip() {
+        let score = mate_in(7);
+
+        assert_eq!(Score::from_tt(score.to_tt(4), 4), score);
+        assert_eq!(Score::from_tt(score.to_tt(4), 2), mate_in(5));
+        assert_eq!(Score::cp(12).to_tt(4), Score::cp(12));
+    }
+
+    #[test]
+    fn serializes_as_cp_or_mate() {
+        assert_eq!(to_string(&Score::cp(35)).unwrap(), r#"{"cp":35}"#);
+        assert_eq!(to_string(&Score::mated_in(6)).unwrap(), r#"{"mate":-3}"#);
+        assert_eq!(mate_in(3).to_string(), "mate 2");
+    }
+}
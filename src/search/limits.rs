@@ -0,0 +1,25 @@
+use crate::board_navigator::Move;
+
+/**
+ * When to stop searching, besides the clock, as UCI's `go` sends it
+ *
+ * The first iteration always completes so that there is a move to play, whatever the limits
+ */
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    /// in plies
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    /// stop once a mate in this many moves or fewer is found, searching no deeper than it needs
+    pub mate: Option<u8>,
+    /// only these root moves are searched, all of them when empty
+    pub searchmoves: Vec<Move>,
+    /// search until stopped, ignoring the clock and found mates
+    pub infinite: bool,
+}
+
+impl SearchLimits {
+    pub fn to_depth(depth: u8) -> SearchLimits {
+        SearchLimits { depth: Some(depth), ..SearchLimits::default() }
+    }
+}
@@ -0,0 +1,22 @@
+// =============
+// Search Module
+// =============
+
+// modules
+mod limits;
+mod move_picker;
+mod options;
+mod searcher;
+mod see;
+mod time_manager;
+mod transposition;
+
+// exports from module
+pub use limits::SearchLimits;
+pub use options::SearchOptions;
+pub use searcher::Searcher;
+pub use searcher::SearchInfo;
+pub use searcher::SearchLine;
+pub use searcher::MAX_DEPTH;
+pub use see::see;
+pub use time_manager::{TimeControl, TimeManager};
@@ -0,0 +1,270 @@
+use crate::board_navigator::{Move, Piece};
+use crate::pieces::Side;
+use crate::position::Position;
+
+use super::searcher::MAX_DEPTH;
+use super::see::{captured_value, see, SeeValue};
+
+/// History scores are halved once one of them gets this big, so old cutoffs fade out
+const HISTORY_MAX: i32 = 1 << 14;
+
+/**
+ * Quiet moves that caused a beta cutoff, two per ply, as a sibling node is likely to be refuted the same way
+ */
+pub struct KillerMoves {
+    moves: Vec<[Option<Move>; 2]>,
+}
+
+impl KillerMoves {
+    pub fn new() -> KillerMoves {
+        KillerMoves {
+            moves: vec![[None, None]; MAX_DEPTH as usize + 1],
+        }
+    }
+
+    pub fn get(&self, ply: u8) -> [Option<Move>; 2] {
+        self.moves.get(ply as usize).cloned().unwrap_or([None, None])
+    }
+
+    pub fn store(&mut self, ply: u8, mv: &Move) {
+        if let Some(killers) = self.moves.get_mut(ply as usize) {
+            if killers[0].as_ref() != Some(mv) {
+                killers[1] = killers[0].take();
+                killers[0] = Some(mv.clone());
+            }
+        }
+    }
+}
+
+/**
+ * How often each quiet move, by side and from and to square, caused a beta cutoff, weighted by depth
+ */
+pub struct HistoryTable {
+    scores: Vec<i32>,
+}
+
+impl HistoryTable {
+    pub fn new() -> HistoryTable {
+        HistoryTable {
+            scores: vec![0; 2 * 64 * 64],
+        }
+    }
+
+    pub fn score(&self, side: Side, mv: &Move) -> i32 {
+        self.scores[HistoryTable::index(side, mv)]
+    }
+
+    pub fn reward(&mut self, side: Side, mv: &Move, depth: u8) {
+        let index = HistoryTable::index(side, mv);
+        self.scores[index] += depth as i32 * depth as i32;
+
+        if self.scores[index] >= HISTORY_MAX {
+            self.scores.iter_mut().for_each(|score| *score /= 2);
+        }
+    }
+
+    fn index(side: Side, mv: &Move) -> usize {
+        let side = match side {
+            Side::White => 0,
+            Side::Black => 1,
+        };
+        (side * 64 + mv.from.to_index() as usize) * 64 + mv.to.to_index() as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    HashMove,
+    ScoreCaptures,
+    GoodCaptures,
+    Killers,
+    ScoreQuiets,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+/**
+ * Hands out the moves of a node best first: the hash move, captures that don't lose material by MVV-LVA,
+ * the killer moves, quiet moves by history and finally the losing captures
+ *
+ * Each stage is only scored once the earlier ones have been searched, so a cutoff from the hash move
+ * or a capture saves the work of ordering the rest
+ */
+pub struct MovePicker {
+    stage: Stage,
+    /// moves not yet moved into a stage
+    moves: Vec<Move>,
+    hash_move: Option<Move>,
+    killers: [Option<Move>; 2],
+    scored: Vec<(Move, i32)>,
+    bad_captures: Vec<(Move, i32)>,
+}
+
+impl MovePicker {
+    pub fn new(moves: Vec<Move>, hash_move: Option<Move>, killers: [Option<Move>; 2]) -> MovePicker {
+        MovePicker {
+            stage: Stage::HashMove,
+            moves,
+            hash_move,
+            killers,
+            scored: vec![],
+            bad_captures: vec![],
+        }
+    }
+
+    pub fn next(&mut self, position: &Position, history: &HistoryTable) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.stage = Stage::ScoreCaptures;
+                    if let Some(hash_move) = self.hash_move.take() {
+                        if let Some(mv) = self.take(&hash_move) {
+                            return Some(mv);
+                        }
+                    }
+                },
+                Stage::ScoreCaptures => {
+                    let (noisy, quiet) = self.moves.drain(..).partition(|mv: &Move| is_noisy(mv));
+                    self.moves = quiet;
+                    self.scored = noisy.into_iter().map(|mv| {
+                        let score = mvv_lva(position, &mv);
+                        (mv, score)
+                    }).collect();
+                    self.stage = Stage::GoodCaptures;
+                },
+                Stage::GoodCaptures => match pick_best(&mut self.scored) {
+                    Some((mv, score)) => {
+                        if mv.promotion.is_none() && see(position, &mv) < 0 {
+                            self.bad_captures.push((mv, score));
+                        } else {
+                            return Some(mv);
+                        }
+                    },
+                    None => self.stage = Stage::Killers,
+                },
+                Stage::Killers => {
+                    let killer = self.killers.iter_mut().find_map(Option::take);
+                    match killer {
+                        Some(killer) => {
+                            // only played if it's a legal quiet move here too
+                            if let Some(mv) = self.take(&killer) {
+                                return Some(mv);
+                            }
+                        },
+                        None => self.stage = Stage::ScoreQuiets,
+                    }
+                },
+                Stage::ScoreQuiets => {
+                    self.scored = self.moves.drain(..).map(|mv| {
+                        let score = history.score(position.active_colour, &mv);
+                        (mv, score)
+                    }).collect();
+                    self.stage = Stage::Quiets;
+                },
+                Stage::Quiets => match pick_best(&mut self.scored) {
+                    Some((mv, _)) => return Some(mv),
+                    None => self.stage = Stage::BadCaptures,
+                },
+                Stage::BadCaptures => match pick_best(&mut self.bad_captures) {
+                    Some((mv, _)) => return Some(mv),
+                    None => self.stage = Stage::Done,
+                },
+                Stage::Done => return None,
+            }
+        }
+    }
+
+    fn take(&mut self, target: &Move) -> Option<Move> {
+        let index = self.moves.iter().position(|mv| mv == target)?;
+        Some(self.moves.swap_remove(index))
+    }
+}
+
+fn is_noisy(mv: &Move) -> bool {
+    mv.capture || mv.promotion.is_some()
+}
+
+/// Most valuable victim first, then least valuable attacker
+fn mvv_lva(position: &Position, mv: &Move) -> i32 {
+    let attacker = match position.get_piece(mv.from.to_index()) {
+        Some(Piece::P) => 1,
+        Some(Piece::N) => 2,
+        Some(Piece::B) => 3,
+        Some(Piece::R) => 4,
+        Some(Piece::Q) => 5,
+        _ => 6,
+    };
+    let promotion = mv.promotion.map_or(0, |piece| SeeValue::of(piece) - SeeValue::PAWN);
+
+    (captured_value(position, mv) + promotion) * 8 - attacker
+}
+
+/// Removes the highest scoring move, a selection sort done one step at a time
+fn pick_best(moves: &mut Vec<(Move, i32)>) -> Option<(Move, i32)> {
+    let best = moves.iter().enumerate().max_by_key(|(_, (_, score))| *score)?.0;
+    Some(moves.swap_remove(best))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HistoryTable, KillerMoves, MovePicker};
+    use crate::board_navigator::{get_legal_moves, parse_uci_move, Move};
+    use crate::fen::from_fen;
+    use crate::position::Position;
+
+    fn picked(position: &Position, picker: &mut MovePicker, history: &HistoryTable) -> Vec<String> {
+        let mut moves = vec![];
+        while let Some(mv) = picker.next(position, history) {
+            moves.push(mv.to_uci());
+        }
+        moves
+    }
+
+    fn uci(position: &Position, mv: &str) -> Move {
+        parse_uci_move(position, mv).unwrap()
+    }
+
+    #[test]
+    fn stages_come_in_order() {
+        // Rxh8 takes an undefended rook, Nxf6 trades knights and Qxd5 loses the queen for a pawn
+        let position = from_fen("4k2r/6p1/4pn2/3p4/4N3/8/3Q3R/4K3 w - -");
+        let mut history = HistoryTable::new();
+        history.reward(position.active_colour, &uci(&position, "e1f1"), 4);
+
+        let mut picker = MovePicker::new(
+            get_legal_moves(&position),
+            Some(uci(&position, "d2d3")),
+            [Some(uci(&position, "h2h4")), None],
+        );
+        let moves = picked(&position, &mut picker, &history);
+
+        assert_eq!(&moves[..4], ["d2d3", "h2h8", "e4f6", "h2h4"]);
+        assert_eq!(moves[4], "e1f1");
+        assert_eq!(moves.last().unwrap(), "d2d5");
+        assert_eq!(moves.len(), get_legal_moves(&position).len());
+    }
+
+    #[test]
+    fn illegal_killers_are_skipped() {
+        let position = from_fen("4k3/8/8/8/8/8/8/4K2R w - -");
+        let killer = uci(&from_fen("4k3/8/8/8/8/8/8/R3K3 w - -"), "a1a8");
+
+        let mut picker = MovePicker::new(get_legal_moves(&position), None, [Some(killer), None]);
+
+        assert_eq!(picked(&position, &mut picker, &HistoryTable::new()).len(), get_legal_moves(&position).len());
+    }
+
+    #[test]
+    fn killers_keep_the_two_latest() {
+        let position = from_fen("4k3/8/8/8/8/8/8/4K2R w - -");
+        let mut killers = KillerMoves::new();
+
+        killers.store(3, &uci(&position, "h1h2"));
+        killers.store(3, &uci(&position, "h1h3"));
+        killers.store(3, &uci(&position, "h1h3"));
+
+        let stored = killers.get(3).map(|mv| mv.unwrap().to_uci());
+        assert_eq!(stored, ["h1h3", "h1h2"]);
+    }
+}
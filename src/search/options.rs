@@ -0,0 +1,86 @@
+use rocket::fairing::AdHoc;
+use rocket::serde::Deserialize;
+
+/**
+ * Switches for the parts of the search that trade accuracy for speed, so each can be A/B tested,
+ * and the number of threads to search with
+ *
+ * Set in the Rocket config under `search`, missing fields take their default
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct SearchOptions {
+    /// Lazy SMP threads sharing the transposition table, a single thread searches deterministically
+    pub threads: usize,
+    /// how many of the best lines to search and report, each one a full search of the root
+    pub multi_pv: usize,
+    /// also search quiet moves that give check at the first ply of quiescence
+    pub quiescence_checks: bool,
+    /// principal variation search, later moves get a zero window and are re-searched if they beat alpha
+    pub pvs: bool,
+    /// give the opponent a free move, if they still can't get below beta the node is cut
+    pub null_move: bool,
+    /// search late quiet moves to a lower depth first
+    pub lmr: bool,
+    /// search a ply deeper after a checking move
+    pub check_extensions: bool,
+    /// skip quiet moves near the leaves that can't raise the score up to alpha
+    pub futility: bool,
+    /// cut nodes near the leaves whose static evaluation is far above beta
+    pub reverse_futility: bool,
+}
+
+impl SearchOptions {
+    /// Plain alpha-beta, to compare the other options against
+    pub fn all_disabled() -> SearchOptions {
+        SearchOptions {
+            threads: 1,
+            multi_pv: 1,
+            quiescence_checks: false,
+            pvs: false,
+            null_move: false,
+            lmr: false,
+            check_extensions: false,
+            futility: false,
+            reverse_futility: false,
+        }
+    }
+
+    /// Manages the options from `search` in the Rocket config, or the defaults
+    pub fn fairing() -> AdHoc {
+        AdHoc::try_on_ignite("Search options", |rocket| async {
+            let options = match rocket.figment().find_value("search") {
+                Ok(_) => rocket.figment().extract_inner::<SearchOptions>("search"),
+                Err(_) => Ok(SearchOptions::default()),
+            };
+
+            match options {
+                Ok(options) if options.threads == 0 || options.multi_pv == 0 => {
+                    rocket::error!("Invalid search options: threads and multi_pv must be at least 1");
+                    Err(rocket)
+                },
+                Ok(options) => Ok(rocket.manage(options)),
+                Err(error) => {
+                    rocket::error!("Invalid search options: {}", error);
+                    Err(rocket)
+                },
+            }
+        })
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> SearchOptions {
+        SearchOptions {
+            threads: 1,
+            multi_pv: 1,
+            quiescence_checks: false,
+            pvs: true,
+            null_move: true,
+            lmr: true,
+            check_extensions: true,
+            futility: true,
+            reverse_futility: true,
+        }
+    }
+}
@@ -0,0 +1,914 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::cmp::Reverse;
+use std::sync::Arc;
+use std::thread;
+
+use rocket::serde::Serialize;
+
+use crate::board_navigator::{get_legal_moves, is_in_check, make_move, Move};
+use crate::evaluator::{evaluate_position, EvalParams};
+use crate::pieces::Pieces;
+use crate::position::Position;
+use crate::score::Score;
+use crate::zobrist::zobrist_key;
+
+use super::limits::SearchLimits;
+use super::move_picker::{HistoryTable, KillerMoves, MovePicker};
+use super::options::SearchOptions;
+use super::see::{captured_value, see};
+use super::time_manager::TimeManager;
+use super::transposition::{Bound, TTEntry, TranspositionTable};
+
+pub const MAX_DEPTH: u8 = 64;
+
+const TT_SIZE_MB: usize = 16;
+
+/// How often the clock is looked at, in nodes
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+/// Iterations from this depth on start with a window around the previous score
+const ASPIRATION_MIN_DEPTH: u8 = 4;
+const ASPIRATION_WINDOW: i32 = 25;
+
+/// Null move searches this many plies less, and more at high depths
+const NULL_MOVE_REDUCTION: u8 = 2;
+const NULL_MOVE_MIN_DEPTH: u8 = 3;
+
+/// Late moves are reduced from the LMR_MIN_MOVES-th move on, by two plies after twice that many
+const LMR_MIN_DEPTH: u8 = 3;
+const LMR_MIN_MOVES: usize = 3;
+
+/// Margins by depth for futility pruning
+const FUTILITY_MARGINS: [i32; 2] = [150, 300];
+
+const REVERSE_FUTILITY_MAX_DEPTH: u8 = 3;
+const REVERSE_FUTILITY_MARGIN: i32 = 120;
+
+/// Captures that can't bring the score back up to alpha even with this much to spare are skipped
+const DELTA_MARGIN: i32 = 200;
+
+/**
+ * Progress of the search, reported after every completed iteration
+ */
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SearchInfo {
+    pub depth: u8,
+    /// from the side to move's point of view
+    pub score: Score,
+    /// principal variation in long algebraic notation
+    pub pv: Vec<String>,
+    pub nodes: u64,
+    pub nps: u64,
+    /// permille
+    pub hashfull: u32,
+    /// milliseconds
+    pub time: u64,
+    /// which of the best lines this is, from 1
+    pub multipv: usize,
+}
+
+/// One of the best lines from the root, from the side to move's point of view
+#[derive(Debug, Clone)]
+pub struct SearchLine {
+    pub score: Score,
+    pub pv: Vec<Move>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: Score,
+    pub depth: u8,
+    /// the best `multi_pv` lines of the last completed iteration, best first
+    pub lines: Vec<SearchLine>,
+}
+
+/**
+ * Iterative deepening alpha-beta search
+ *
+ * With more than one thread in its options it runs a Lazy SMP search: helper threads search the same
+ * position, some a ply deeper, filling the shared transposition table with results the main thread
+ * picks up. Only the main thread reports and decides the move.
+ */
+pub struct Searcher {
+    tt: Arc<TranspositionTable>,
+    params: EvalParams,
+    options: SearchOptions,
+    killers: KillerMoves,
+    history: HistoryTable,
+    stop: Arc<AtomicBool>,
+    time: TimeManager,
+    /// the hard deadline or the node limit was hit
+    timed_out: bool,
+    /// of the main thread
+    node_limit: Option<u64>,
+    /// the moves searched at the root, narrowed down by `searchmoves`
+    root_moves: Vec<Move>,
+    /// the node being searched was reached by a null move, so another is not allowed
+    after_null_move: bool,
+    /// root moves of lines already found in this iteration
+    excluded_root_moves: Vec<Move>,
+    nodes: u64,
+    /// nodes searched by the helper threads, added in batches
+    helper_nodes: Arc<AtomicU64>,
+    completed_depth: u8,
+    /// of the iteration being searched, checks are only extended within twice it so a run of checks can't
+    /// grow the tree without end
+    root_depth: u8,
+    /// 0 for the main thread
+    thread_id: usize,
+}
+
+impl Searcher {
+    pub fn new(stop: Arc<AtomicBool>, params: EvalParams) -> Searcher {
+        Searcher {
+            tt: Arc::new(TranspositionTable::new(TT_SIZE_MB)),
+            params,
+            options: SearchOptions::default(),
+            killers: KillerMoves::new(),
+            history: HistoryTable::new(),
+            stop,
+            time: TimeManager::unlimited(),
+            timed_out: false,
+            node_limit: None,
+            root_moves: vec![],
+            after_null_move: false,
+            excluded_root_moves: vec![],
+            nodes: 0,
+            helper_nodes: Arc::new(AtomicU64::new(0)),
+            completed_depth: 0,
+            root_depth: 0,
+            thread_id: 0,
+        }
+    }
+
+    pub fn with_options(mut self, options: SearchOptions) -> Searcher {
+        self.options = options;
+        self
+    }
+
+    /// Iteratively deepens until one of the limits or the time runs out, calling `on_info` after each completed
+    /// iteration. Setting the stop flag ends the search, keeping the result of the last completed iteration.
+    pub fn search<F: FnMut(&SearchInfo)>(&mut self, position: &Position, limits: &SearchLimits, time: TimeManager, on_info: F) -> SearchResult {
+        self.time = if limits.infinite { TimeManager::unlimited() } else { time };
+        self.node_limit = limits.nodes;
+        self.root_moves = get_legal_moves(position);
+
+        // moves that aren't legal here are left out, and if none are the limit is ignored
+        if limits.searchmoves.iter().any(|mv| self.root_moves.contains(mv)) {
+            self.root_moves.retain(|mv| limits.searchmoves.contains(mv));
+        }
+
+        // a mate in n moves is at most 2n - 1 plies away
+        let max_depth = match (limits.depth, limits.mate) {
+            (Some(depth), _) => depth,
+            (None, Some(mate)) => mate.saturating_mul(2).saturating_sub(1),
+            (None, None) => MAX_DEPTH,
+        };
+
+        self.timed_out = false;
+        self.nodes = 0;
+        self.helper_nodes.store(0, Ordering::Relaxed);
+        self.completed_depth = 0;
+        self.killers = KillerMoves::new();
+        self.history = HistoryTable::new();
+
+        // a mate search has to be exact to its depth, so nothing that can hide a mate is done
+        let options = self.options.clone();
+        if limits.mate.is_some() {
+            self.options = SearchOptions {
+                null_move: false,
+                lmr: false,
+                futility: false,
+                reverse_futility: false,
+                ..options.clone()
+            };
+        }
+
+        let result = if self.options.threads <= 1 {
+            self.iterate(position, max_depth, limits, on_info)
+        } else {
+            self.iterate_with_helpers(position, max_depth, limits, on_info)
+        };
+
+        self.options = options;
+        result
+    }
+
+    fn iterate_with_helpers<F: FnMut(&SearchInfo)>(&mut self, position: &Position, max_depth: u8, limits: &SearchLimits, on_info: F) -> SearchResult {
+        // the helpers run until the main thread is done with the search
+        let helpers_stop = Arc::new(AtomicBool::new(false));
+
+        thread::scope(|scope| {
+            for thread_id in 1..self.options.threads {
+                let mut helper = self.helper(thread_id, helpers_stop.clone());
+                scope.spawn(move || helper.iterate(position, max_depth, limits, |_| ()));
+            }
+
+            let result = self.iterate(position, max_depth, limits, on_info);
+            helpers_stop.store(true, Ordering::Relaxed);
+            result
+        })
+    }
+
+    /// A searcher for a helper thread, sharing the transposition table and the clock
+    fn helper(&self, thread_id: usize, stop: Arc<AtomicBool>) -> Searcher {
+        Searcher {
+            tt: self.tt.clone(),
+            params: self.params.clone(),
+            options: self.options.clone(),
+            killers: KillerMoves::new(),
+            history: HistoryTable::new(),
+            stop,
+            time: self.time.clone(),
+            timed_out: false,
+            node_limit: None,
+            root_moves: self.root_moves.clone(),
+            after_null_move: false,
+            excluded_root_moves: vec![],
+            nodes: 0,
+            helper_nodes: self.helper_nodes.clone(),
+            completed_depth: 0,
+            root_depth: 0,
+            thread_id,
+        }
+    }
+
+    /// Deepens one ply at a time, searching the root once for each of the best lines with the moves
+    /// of the lines already found left out
+    fn iterate<F: FnMut(&SearchInfo)>(&mut self, position: &Position, max_depth: u8, limits: &SearchLimits, mut on_info: F) -> SearchResult {
+        let max_depth = max_depth.clamp(1, MAX_DEPTH);
+        let line_count = self.options.multi_pv.clamp(1, self.root_moves.len().max(1));
+        let mut lines: Vec<SearchLine> = vec![];
+        let mut completed_depth = 0;
+
+        for depth in 1..=max_depth {
+            // half of the helpers search a ply ahead so the threads don't all do the same work
+            let depth = (depth + (self.thread_id % 2) as u8).min(max_depth);
+            let mut iteration = vec![];
+
+            self.root_depth = depth;
+            self.excluded_root_moves.clear();
+            for index in 0..line_count {
+                let mut pv = vec![];
+                let score = match lines.get(index) {
+                    Some(line) if depth >= ASPIRATION_MIN_DEPTH && !line.score.is_mate() => {
+                        self.aspiration_search(position, depth, line.score, &mut pv)
+                    },
+                    _ => self.negamax(position, depth, -Score::INFINITY, Score::INFINITY, 0, &mut pv),
+                };
+
+                if self.should_stop() {
+                    break;
+                }
+
+                // mate or stalemate at the root
+                if pv.is_empty() {
+                    iteration.push(SearchLine { score, pv });
+                    break;
+                }
+
+                self.excluded_root_moves.push(pv[0].clone());
+                iteration.push(SearchLine { score, pv });
+            }
+            self.excluded_root_moves.clear();
+
+            // a line cut short by the stop flag is discarded along with the rest of the iteration
+            if self.should_stop() {
+                break;
+            }
+
+            // with pruning a later line can come out slightly ahead of an earlier one
+            iteration.sort_by_key(|line| Reverse(line.score));
+
+            self.completed_depth = depth;
+            completed_depth = depth;
+            lines = iteration;
+
+            let elapsed = self.time.elapsed().as_millis() as u64;
+            let nodes = self.nodes + self.helper_nodes.load(Ordering::Relaxed);
+            for (index, line) in lines.iter().enumerate() {
+                on_info(&SearchInfo {
+                    depth,
+                    score: line.score,
+                    pv: line.pv.iter().map(Move::to_uci).collect(),
+                    nodes,
+                    nps: nodes * 1000 / elapsed.max(1),
+                    hashfull: self.tt.hashfull(),
+                    time: elapsed,
+                    multipv: index + 1,
+                });
+            }
+
+            let mate_found = match limits.mate {
+                Some(mate) => lines[0].score.mate_moves().is_some_and(|moves| moves > 0 && moves <= mate as i32),
+                // no need to look deeper once every line ends in a forced mate
+                None => lines.iter().all(|line| line.score.is_mate()),
+            };
+
+            if lines[0].pv.is_empty() || (!limits.infinite && (mate_found || self.time.soft_expired())) {
+                break;
+            }
+        }
+
+        SearchResult {
+            best_move: lines.first().and_then(|line| line.pv.first().cloned()),
+            score: lines.first().map_or(Score::DRAW, |line| line.score),
+            depth: completed_depth,
+            lines,
+        }
+    }
+
+    /// Searches a narrow window around the previous iteration's score, widening it on the side
+    /// the score falls outside of until it lands inside
+    fn aspiration_search(&mut self, position: &Position, depth: u8, guess: Score, pv: &mut Vec<Move>) -> Score {
+        let mut delta = ASPIRATION_WINDOW;
+        let mut alpha = Score((guess.0 - delta).max(-Score::INFINITY.0));
+        let mut beta = Score((guess.0 + delta).min(Score::INFINITY.0));
+
+        loop {
+            pv.clear();
+            let score = self.negamax(position, depth, alpha, beta, 0, pv);
+
+            if self.should_stop() {
+                return score;
+            }
+
+            delta *= 2;
+            if score <= alpha {
+                alpha = Score((score.0 - delta).max(-Score::INFINITY.0));
+            } else if score >= beta {
+                beta = Score((score.0 + delta).min(Score::INFINITY.0));
+            } else {
+                return score;
+            }
+        }
+    }
+
+    /// The first iteration always completes so that there is a move to play
+    fn should_stop(&self) -> bool {
+        self.completed_depth > 0 && (self.timed_out || self.stop.load(Ordering::Relaxed))
+    }
+
+    fn count_node(&mut self) {
+        self.nodes += 1;
+
+        if self.nodes.is_multiple_of(TIME_CHECK_INTERVAL) {
+            if self.thread_id > 0 {
+                self.helper_nodes.fetch_add(TIME_CHECK_INTERVAL, Ordering::Relaxed);
+            }
+            if self.time.hard_expired() {
+                self.timed_out = true;
+            }
+        }
+
+        if self.node_limit.is_some_and(|limit| self.nodes >= limit) {
+            self.timed_out = true;
+        }
+    }
+
+    fn negamax(&mut self, position: &Position, depth: u8, mut alpha: Score, beta: Score, ply: u8, pv: &mut Vec<Move>) -> Score {
+        self.count_node();
+
+        if self.should_stop() {
+            return Score::DRAW;
+        }
+
+        if ply > 0 && position.half_move_clock >= 100 {
+            return Score::DRAW;
+        }
+
+        if depth == 0 || ply >= MAX_DEPTH {
+            return self.quiesce(position, alpha, beta, ply, 0, &mut vec![]);
+        }
+
+        let key = zobrist_key(position);
+        let mut hash_move = None;
+
+        if let Some(entry) = self.tt.probe(key) {
+            hash_move = entry.best_move.clone();
+            let score = Score::from_tt(entry.score, ply);
+
+            if ply > 0 && entry.depth >= depth {
+                let is_cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                if is_cutoff {
+                    return score;
+                }
+            }
+        }
+
+        let mut moves = get_legal_moves(position);
+        let in_check = is_in_check(position);
+
+        if moves.is_empty() {
+            return if in_check { Score::mated_in(ply) } else { Score::DRAW };
+        }
+
+        let mut is_partial_root = false;
+        if ply == 0 {
+            let legal_moves = moves.len();
+            moves.retain(|mv| self.root_moves.contains(mv) && !self.excluded_root_moves.contains(mv));
+            is_partial_root = moves.len() < legal_moves;
+        }
+
+        let is_pv_node = beta.0 - alpha.0 > 1;
+        let static_eval = evaluate_position(position, &self.params);
+        let can_prune = ply > 0 && !is_pv_node && !in_check && !beta.is_mate();
+
+        // reverse futility: even a big loss wouldn't bring the score back below beta
+        if self.options.reverse_futility && can_prune && depth <= REVERSE_FUTILITY_MAX_DEPTH &&
+            static_eval.0 - REVERSE_FUTILITY_MARGIN * depth as i32 >= beta.0 {
+            return static_eval;
+        }
+
+        // null move: if passing still fails high, a real move almost certainly would too. In pawn endings
+        // passing is often the best move there is (zugzwang), so it isn't tried there
+        if self.options.null_move && can_prune && depth >= NULL_MOVE_MIN_DEPTH && static_eval >= beta &&
+            !self.after_null_move && has_non_pawn_material(position) {
+            let reduction = NULL_MOVE_REDUCTION + depth / 6;
+
+            self.after_null_move = true;
+            let score = -self.negamax(&null_move(position), depth.saturating_sub(1 + reduction), -beta, Score(1 - beta.0), ply + 1, &mut vec![]);
+            self.after_null_move = false;
+
+            if self.should_stop() {
+                return Score::DRAW;
+            }
+            if score >= beta {
+                return if score.is_mate() { beta } else { score };
+            }
+        }
+        self.after_null_move = false;
+
+        let futility_pruning = self.options.futility && can_prune && depth <= FUTILITY_MARGINS.len() as u8 &&
+            static_eval.0 + FUTILITY_MARGINS[depth as usize - 1] <= alpha.0;
+
+        let mut picker = MovePicker::new(moves, hash_move, self.killers.get(ply));
+
+        let original_alpha = alpha;
+        let mut best_score = -Score::INFINITY;
+        let mut best_move = None;
+        let mut moves_searched = 0;
+
+        while let Some(mv) = picker.next(position, &self.history) {
+            let mut next = position.clone();
+            make_move(&mut next, &mv);
+
+            let is_quiet = !mv.capture && mv.promotion.is_none();
+            let gives_check = is_in_check(&next);
+
+            if futility_pruning && moves_searched > 0 && is_quiet && !gives_check {
+                continue;
+            }
+
+            let extension = (self.options.check_extensions && gives_check && ply < self.root_depth * 2) as u8;
+            let new_depth = depth - 1 + extension;
+
+            let mut child_pv = vec![];
+            let score = if moves_searched == 0 {
+                -self.negamax(&next, new_depth, -beta, -alpha, ply + 1, &mut child_pv)
+            } else {
+                let reduction = if self.options.lmr && is_quiet && !in_check && !gives_check &&
+                    depth >= LMR_MIN_DEPTH && moves_searched >= LMR_MIN_MOVES {
+                    (1 + (moves_searched >= LMR_MIN_MOVES * 2) as u8).min(new_depth - 1)
+                } else {
+                    0
+                };
+
+                // with PVS, later moves only need to prove they can't beat alpha
+                let window = if self.options.pvs { Score(alpha.0 + 1) } else { beta };
+
+                let mut score = -self.negamax(&next, new_depth - reduction, -window, -alpha, ply + 1, &mut child_pv);
+
+                if reduction > 0 && score > alpha {
+                    child_pv.clear();
+                    score = -self.negamax(&next, new_depth, -window, -alpha, ply + 1, &mut child_pv);
+                }
+                if window < beta && score > alpha && score < beta {
+                    child_pv.clear();
+                    score = -self.negamax(&next, new_depth, -beta, -alpha, ply + 1, &mut child_pv);
+                }
+                score
+            };
+
+            moves_searched += 1;
+
+            if self.should_stop() {
+                return Score::DRAW;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv.clone());
+
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(mv.clone());
+                    pv.append(&mut child_pv);
+                }
+
+                if alpha >= beta {
+                    // quiet moves that refute the opponent's move are worth trying early elsewhere
+                    if is_quiet {
+                        self.killers.store(ply, &mv);
+                        self.history.reward(position.active_colour, &mv, depth);
+                    }
+                    break;
+                }
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+
+        // the best of a subset of the root moves isn't the position's score
+        if is_partial_root {
+            return best_score;
+        }
+
+        self.tt.store(TTEntry {
+            key,
+            depth,
+            score: best_score.to_tt(ply),
+            bound,
+            best_move,
+        });
+
+        best_score
+    }
+
+    /// Quiescence search alone with a full window, along with the line of captures it settles on
+    pub fn quiescence(&mut self, position: &Position) -> (Score, Vec<Move>) {
+        let mut pv = vec![];
+        let score = self.quiesce(position, -Score::INFINITY, Score::INFINITY, 0, 0, &mut pv);
+
+        (score, pv)
+    }
+
+    /// Searches captures and promotions until the position is quiet, so the evaluation at the
+    /// leaves isn't taken in the middle of an exchange. In check every evasion is searched instead.
+    fn quiesce(&mut self, position: &Position, mut alpha: Score, beta: Score, ply: u8, quiescence_ply: u8, pv: &mut Vec<Move>) -> Score {
+        self.count_node();
+
+        if self.should_stop() {
+            return Score::DRAW;
+        }
+
+        let in_check = is_in_check(position);
+        let stand_pat = evaluate_position(position, &self.params);
+
+        if ply >= MAX_DEPTH * 2 {
+            return stand_pat;
+        }
+
+        // the side to move can usually do at least as well as the static evaluation by not capturing
+        if !in_check {
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            if stand_pat > alpha {
+                alpha = stand_pat;
+            }
+        }
+
+        let include_checks = !in_check && quiescence_ply == 0 && self.options.quiescence_checks;
+        let mut moves = get_legal_moves(position);
+
+        if in_check && moves.is_empty() {
+            return Score::mated_in(ply);
+        }
+
+        if !in_check {
+            moves.retain(|mv| mv.capture || mv.promotion.is_some() || (include_checks && gives_check(position, mv)));
+        }
+
+        let mut picker = MovePicker::new(moves, None, [None, None]);
+        let mut best_score = if in_check { -Score::INFINITY } else { stand_pat };
+
+        while let Some(mv) = picker.next(position, &self.history) {
+            if !in_check && mv.promotion.is_none() && mv.capture {
+                // delta pruning, even winning the piece wouldn't be enough
+                if stand_pat.0 + captured_value(position, &mv) + DELTA_MARGIN < alpha.0 {
+                    continue;
+                }
+
+                // the exchange on the square loses material
+                if see(position, &mv) < 0 {
+                    continue;
+                }
+            }
+
+            let mut next = position.clone();
+            make_move(&mut next, &mv);
+
+            let mut child_pv = vec![];
+            let score = -self.quiesce(&next, -beta, -alpha, ply + 1, quiescence_ply + 1, &mut child_pv);
+
+            if self.should_stop() {
+                return Score::DRAW;
+            }
+
+            if score > best_score {
+                best_score = score;
+
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(mv.clone());
+                    pv.append(&mut child_pv);
+                }
+
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        best_score
+    }
+}
+
+/// The same position with the other side to move
+fn null_move(position: &Position) -> Position {
+    let mut next = position.clone();
+    next.active_colour = next.active_colour.opponent();
+    next.en_passant_target = None;
+    next.half_move_clock += 1;
+    next
+}
+
+/// Anything besides the king and pawns for the side to move
+fn has_non_pawn_material(position: &Position) -> bool {
+    let side = position.active_colour;
+    let pawns_and_king = position.get_coloured_pieces(side, Pieces::PAWN) | position.get_coloured_pieces(side, Pieces::KING);
+    position.get_side_pieces(side) != pawns_and_king
+}
+
+fn gives_check(position: &Position, mv: &Move) -> bool {
+    let mut next = position.clone();
+    make_move(&mut next, mv);
+    is_in_check(&next)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    use super::{has_non_pawn_material, Searcher};
+    use crate::evaluator::EvalParams;
+    use crate::board_navigator::parse_uci_move;
+    use crate::search::{SearchLimits, SearchOptions, TimeControl, TimeManager};
+    use crate::pieces::Side;
+    use crate::fen::from_fen;
+    use crate::score::Score;
+
+    fn searcher() -> Searcher {
+        Searcher::new(Arc::new(AtomicBool::new(false)), EvalParams::default())
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        let position = from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -");
+
+        let result = searcher().search(&position, &SearchLimits::to_depth(2), TimeManager::unlimited(), |_| ());
+
+        assert_eq!(result.best_move.unwrap().to_uci(), "a1a8");
+        assert_eq!(result.score, -Score::mated_in(1));
+    }
+
+    #[test]
+    fn captures_hanging_queen() {
+        let position = from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - -");
+
+        let result = searcher().search(&position, &SearchLimits::to_depth(2), TimeManager::unlimited(), |_| ());
+
+        assert_eq!(result.best_move.unwrap().to_uci(), "d2d5");
+    }
+
+    #[test]
+    fn reports_info_for_each_iteration() {
+        let position = from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - -");
+        let mut depths = vec![];
+
+        searcher().search(&position, &SearchLimits::to_depth(3), TimeManager::unlimited(), |info| depths.push(info.depth));
+
+        assert_eq!(depths, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn stop_flag_keeps_first_iteration() {
+        let position = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+        let mut searcher = Searcher::new(Arc::new(AtomicBool::new(true)), EvalParams::default());
+
+        let result = searcher.search(&position, &SearchLimits::to_depth(10), TimeManager::unlimited(), |_| ());
+
+        assert_eq!(result.depth, 1);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn quiescence_sees_the_recapture() {
+        // the pawn on d5 is defended, taking it with the queen loses the queen
+        let position = from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - -");
+
+        let result = searcher().search(&position, &SearchLimits::to_depth(1), TimeManager::unlimited(), |_| ());
+
+        assert_ne!(result.best_move.unwrap().to_uci(), "d1d5");
+    }
+
+    #[test]
+    fn quiescence_checks_are_optional() {
+        let position = from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -");
+        let mut without_checks = searcher();
+        let mut with_checks = searcher().with_options(SearchOptions { quiescence_checks: true, ..SearchOptions::default() });
+
+        assert!(!without_checks.quiescence(&position).0.is_mate());
+        assert_eq!(with_checks.quiescence(&position).0, -Score::mated_in(1));
+    }
+
+    #[test]
+    fn stops_at_the_movetime() {
+        let position = from_fen("r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - -");
+        let time = TimeManager::new(&TimeControl { movetime: Some(300), ..TimeControl::default() }, Side::White);
+
+        let start = Instant::now();
+        let result = searcher().search(&position, &SearchLimits::default(), time, |_| ());
+
+        assert!(start.elapsed().as_millis() < 1000, "took {:?}", start.elapsed());
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn aspiration_windows_agree_with_a_full_window() {
+        let position = from_fen("r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - -");
+        let mut searcher = searcher();
+
+        let guess = searcher.search(&position, &SearchLimits::to_depth(3), TimeManager::unlimited(), |_| ()).score;
+        let full = searcher.negamax(&position, 4, -Score::INFINITY, Score::INFINITY, 0, &mut vec![]);
+        let aspiration = searcher.aspiration_search(&position, 4, guess, &mut vec![]);
+
+        assert_eq!(aspiration, full);
+    }
+
+    #[test]
+    fn pruning_finds_the_same_tactics() {
+        for options in [SearchOptions::default(), SearchOptions::all_disabled()] {
+            let mate = from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -");
+            let result = searcher().with_options(options.clone()).search(&mate, &SearchLimits::to_depth(4), TimeManager::unlimited(), |_| ());
+            assert_eq!(result.best_move.unwrap().to_uci(), "a1a8", "{:?}", options);
+
+            let queen = from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - -");
+            let result = searcher().with_options(options.clone()).search(&queen, &SearchLimits::to_depth(4), TimeManager::unlimited(), |_| ());
+            assert_eq!(result.best_move.unwrap().to_uci(), "d2d5", "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn pruning_searches_fewer_nodes() {
+        let position = from_fen("r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - -");
+
+        let mut pruned = searcher();
+        pruned.search(&position, &SearchLimits::to_depth(4), TimeManager::unlimited(), |_| ());
+        let mut plain = searcher().with_options(SearchOptions::all_disabled());
+        plain.search(&position, &SearchLimits::to_depth(4), TimeManager::unlimited(), |_| ());
+
+        let (pruned, plain) = (pruned.nodes, plain.nodes);
+
+        assert!(pruned < plain, "{} >= {}", pruned, plain);
+    }
+
+    #[test]
+    fn no_null_move_with_only_king_and_pawns() {
+        assert!(!has_non_pawn_material(&from_fen("8/4k3/4p3/4P3/4K3/8/8/8 w - -")));
+        assert!(has_non_pawn_material(&from_fen("8/4k3/4p3/4P3/4K3/8/8/7N w - -")));
+        assert!(!has_non_pawn_material(&from_fen("8/4k3/4p3/4P3/4K3/8/8/7n w - -")));
+    }
+
+    #[test]
+    fn single_thread_is_deterministic() {
+        let position = from_fen("r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - -");
+        let mut infos = vec![];
+
+        for _ in 0..2 {
+            let mut pvs = vec![];
+            searcher().search(&position, &SearchLimits::to_depth(4), TimeManager::unlimited(), |info| pvs.push((info.nodes, info.pv.clone())));
+            infos.push(pvs);
+        }
+
+        assert_eq!(infos[0], infos[1]);
+    }
+
+    #[test]
+    fn helper_threads_share_the_search() {
+        let options = SearchOptions { threads: 4, ..SearchOptions::default() };
+
+        let mate = from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -");
+        let result = searcher().with_options(options.clone()).search(&mate, &SearchLimits::to_depth(4), TimeManager::unlimited(), |_| ());
+        assert_eq!(result.best_move.unwrap().to_uci(), "a1a8");
+        assert_eq!(result.score, -Score::mated_in(1));
+
+        let queen = from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - -");
+        let mut depths = vec![];
+        let result = searcher().with_options(options).search(&queen, &SearchLimits::to_depth(4), TimeManager::unlimited(), |info| depths.push(info.depth));
+        assert_eq!(result.best_move.unwrap().to_uci(), "d2d5");
+        assert_eq!(depths, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn multi_pv_finds_distinct_lines_best_first() {
+        let position = from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - -");
+        let options = SearchOptions { multi_pv: 3, ..SearchOptions::default() };
+        let mut reported = vec![];
+
+        let result = searcher().with_options(options).search(&position, &SearchLimits::to_depth(3), TimeManager::unlimited(), |info| {
+            reported.push((info.depth, info.multipv));
+        });
+
+        let root_moves: Vec<String> = result.lines.iter().map(|line| line.pv[0].to_uci()).collect();
+        assert_eq!(root_moves.len(), 3);
+        assert_eq!(root_moves[0], "d2d5");
+        assert!(root_moves[1] != root_moves[2] && !root_moves[1..].contains(&root_moves[0]));
+        assert!(result.lines.windows(2).all(|lines| lines[0].score >= lines[1].score));
+        assert_eq!(result.score, result.lines[0].score);
+        assert_eq!(&reported[6..], [(3, 1), (3, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn multi_pv_is_capped_at_the_legal_moves() {
+        // the knight covers b7, leaving the king a7 and b8
+        let position = from_fen("k7/8/8/2N5/8/8/8/7K b - -");
+        let options = SearchOptions { multi_pv: 5, ..SearchOptions::default() };
+
+        let result = searcher().with_options(options).search(&position, &SearchLimits::to_depth(2), TimeManager::unlimited(), |_| ());
+
+        assert_eq!(result.lines.len(), 2);
+    }
+
+    #[test]
+    fn node_limited_search_is_reproducible() {
+        let position = from_fen("r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - -");
+        let limits = SearchLimits { nodes: Some(3000), ..SearchLimits::default() };
+
+        let mut first = searcher();
+        let mut second = searcher();
+        let first_result = first.search(&position, &limits, TimeManager::unlimited(), |_| ());
+        let second_result = second.search(&position, &limits, TimeManager::unlimited(), |_| ());
+
+        assert_eq!(first.nodes, second.nodes);
+        assert_eq!((first_result.best_move, first_result.depth), (second_result.best_move, second_result.depth));
+        assert!(first.nodes <= 3001, "{}", first.nodes);
+    }
+
+    #[test]
+    fn searchmoves_restricts_the_root() {
+        let position = from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - -");
+        let limits = SearchLimits {
+            depth: Some(3),
+            searchmoves: vec![parse_uci_move(&position, "d1c1").unwrap(), parse_uci_move(&position, "d2d3").unwrap()],
+            ..SearchLimits::default()
+        };
+        let options = SearchOptions { multi_pv: 3, ..SearchOptions::default() };
+
+        let result = searcher().with_options(options).search(&position, &limits, TimeManager::unlimited(), |_| ());
+
+        let mut root_moves: Vec<String> = result.lines.iter().map(|line| line.pv[0].to_uci()).collect();
+        root_moves.sort();
+        assert_eq!(root_moves, ["d1c1", "d2d3"]);
+    }
+
+    #[test]
+    fn mate_limit_stops_at_the_mate() {
+        // Kb6 leaves the king only b8, then Rh8 mates
+        let position = from_fen("k7/8/2K5/8/8/8/8/7R w - -");
+        let limits = SearchLimits { mate: Some(2), ..SearchLimits::default() };
+
+        let result = searcher().search(&position, &limits, TimeManager::unlimited(), |_| ());
+
+        assert_eq!(result.score.mate_moves(), Some(2));
+        assert!(result.depth <= 3);
+    }
+
+    #[test]
+    fn infinite_search_keeps_going_after_a_mate() {
+        let position = from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -");
+        let limits = SearchLimits { depth: Some(3), infinite: true, ..SearchLimits::default() };
+
+        let result = searcher().search(&position, &limits, TimeManager::unlimited(), |_| ());
+
+        assert_eq!(result.depth, 3);
+        assert_eq!(result.score, -Score::mated_in(1));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::bitboard::BitBoard;
+use crate::board_navigator::{attackers_to, Move, Piece};
+use crate::pieces::{Pieces, Side};
+use crate::position::Position;
+
+/// Piece values for exchanges, only their order and rough size matter
+pub struct SeeValue;
+impl SeeValue {
+    pub const PAWN: i32 = 100;
+    pub const KNIGHT: i32 = 325;
+    pub const BISHOP: i32 = 325;
+    pub const ROOK: i32 = 500;
+    pub const QUEEN: i32 = 975;
+    pub const KING: i32 = 20_000;
+
+    pub fn of(piece: Piece) -> i32 {
+        match piece {
+            Piece::P => SeeValue::PAWN,
+            Piece::N => SeeValue::KNIGHT,
+            Piece::B => SeeValue::BISHOP,
+            Piece::R => SeeValue::ROOK,
+            Piece::Q => SeeValue::QUEEN,
+            Piece::K => SeeValue::KING,
+        }
+    }
+}
+
+/// Cheapest first, the order each side recaptures in
+const RECAPTURE_ORDER: [(usize, i32); 6] = [
+    (Pieces::PAWN, SeeValue::PAWN),
+    (Pieces::KNIGHT, SeeValue::KNIGHT),
+    (Pieces::BISHOP, SeeValue::BISHOP),
+    (Pieces::ROOK, SeeValue::ROOK),
+    (Pieces::QUEEN, SeeValue::QUEEN),
+    (Pieces::KING, SeeValue::KING),
+];
+
+/// Value of the piece the move takes, a pawn for en passant
+pub fn captured_value(position: &Position, mv: &Move) -> i32 {
+    if mv.en_passant {
+        return SeeValue::PAWN;
+    }
+
+    position.get_piece(mv.to.to_index()).map_or(0, SeeValue::of)
+}
+
+/**
+ * Static exchange evaluation: the material the side to move wins, or loses when negative, if both sides
+ * keep recapturing on the move's target square with their cheapest piece for as long as it pays off
+ *
+ * Sliders lined up behind a piece join in once it has captured. Pinned pieces don't recapture while
+ * the piece pinning them is still on the board
+ */
+pub fn see(position: &Position, mv: &Move) -> i32 {
+    let Some(mover) = position.get_piece(mv.from.to_index()) else {
+        return 0;
+    };
+
+    let square = mv.to.to_index() as usize;
+    let mut occupancy = position.get_occupied().0 & !(1 << mv.from.to_index());
+
+    if mv.en_passant {
+        // the captured pawn is behind the target square
+        let captured = match position.active_colour {
+            Side::White => square + 8,
+            Side::Black => square - 8,
+        };
+        occupancy &= !(1 << captured);
+    }
+
+    let mut gains = [0; 32];
+    gains[0] = captured_value(position, mv);
+
+    // the piece standing on the square, next in line to be taken
+    let mut on_square = match mv.promotion {
+        Some(promotion) => {
+            gains[0] += SeeValue::of(promotion) - SeeValue::PAWN;
+            SeeValue::of(promotion)
+        },
+        None => SeeValue::of(mover),
+    };
+
+    let pins = [Side::White, Side::Black].map(|side| position.pins(side));
+    let pins_of = |side: Side| match side {
+        Side::White => pins[0],
+        Side::Black => pins[1],
+    };
+
+    let mut side = position.active_colour.opponent();
+    let mut depth = 0;
+
+    loop {
+        let mut attackers = attackers_to(position, square, side, BitBoard(occupancy)).0;
+
+        let (pinned, pinners) = pins_of(side);
+        if pinners.0 & occupancy != 0 {
+            attackers &= !pinned.0;
+        }
+        let Some((piece, value)) = RECAPTURE_ORDER.into_iter().find(|(piece, _)| attackers & position.pieces[*piece].0 != 0) else {
+            break;
+        };
+
+        // the king can't recapture into a defended square
+        if piece == Pieces::KING && attackers_to(position, square, side.opponent(), BitBoard(occupancy)).0 != 0 {
+            break;
+        }
+
+        depth += 1;
+        gains[depth] = on_square - gains[depth - 1];
+
+        // neither side can do better by carrying on
+        if (-gains[depth - 1]).max(gains[depth]) < 0 || depth == gains.len() - 1 {
+            break;
+        }
+
+        let attacker = attackers & position.pieces[piece].0;
+        occupancy &= !(attacker & attacker.wrapping_neg());
+        on_square = value;
+        side = side.opponent();
+    }
+
+    // either side can stop recapturing when it would lose material
+    while depth > 0 {
+        gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+        depth -= 1;
+    }
+
+    gains[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{see, SeeValue};
+    use crate::board_navigator::parse_uci_move;
+    use crate::fen::from_fen;
+
+    fn see_of(fen: &str, uci: &str) -> i32 {
+        let position = from_fen(fen);
+        see(&position, &parse_uci_move(&position, uci).unwrap())
+    }
+
+    #[test]
+    fn pinned_piece_does_not_recapture() {
+        // the knight on c6 defends the pawn on d4, unless the bishop pins it to the king
+        assert_eq!(see_of("4k3/8/2n5/1B6/3p4/8/8/3RK3 w - -", "d1d4"), SeeValue::PAWN);
+        assert_eq!(see_of("5k2/8/2n5/1B6/3p4/8/8/3RK3 w - -", "d1d4"), SeeValue::PAWN - SeeValue::ROOK);
+    }
+
+    #[test]
+    fn undefended_piece_is_won() {
+        assert_eq!(see_of("4k3/8/8/3r4/8/8/3R4/4K3 w - -", "d2d5"), SeeValue::ROOK);
+    }
+
+    #[test]
+    fn defended_pawn_costs_the_queen() {
+        assert_eq!(see_of("4k3/8/2p5/3p4/8/8/8/3QK3 w - -", "d1d5"), SeeValue::PAWN - SeeValue::QUEEN);
+    }
+
+    #[test]
+    fn x_ray_rook_backs_up_the_capture() {
+        // Rxd5 Nxd5 Rxd5 wins a pawn and a knight for a rook
+        let fen = "4k3/8/5n2/3p4/8/8/3R4/3RK3 w - -";
+
+        assert_eq!(see_of(fen, "d2d5"), SeeValue::PAWN + SeeValue::KNIGHT - SeeValue::ROOK);
+    }
+
+    #[test]
+    fn recapture_is_skipped_when_it_loses() {
+        // after Nxd5 black wouldn't take back with the queen as the bishop recaptures
+        let fen = "3qk3/8/8/3p4/8/2N5/6B1/4K3 w - -";
+
+        assert_eq!(see_of(fen, "c3d5"), SeeValue::PAWN);
+    }
+
+    #[test]
+    fn king_cannot_recapture_a_defended_piece() {
+        assert_eq!(see_of("8/8/8/3pk3/8/8/3R4/3RK3 w - -", "d2d5"), SeeValue::PAWN);
+    }
+}
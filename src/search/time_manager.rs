@@ -0,0 +1,146 @@
+use std::time::{Duration, Instant};
+
+use rocket::serde::Deserialize;
+use rocket::FromForm;
+
+use crate::pieces::Side;
+
+/// Kept back from every move for the time it takes to send the move, in milliseconds
+const MOVE_OVERHEAD: u64 = 50;
+
+/// Moves the remaining time is shared between when `movestogo` isn't given
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+/**
+ * The clock as UCI's `go` sends it, all times in milliseconds
+ */
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, FromForm)]
+#[serde(crate = "rocket::serde")]
+pub struct TimeControl {
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    /// search exactly this long, ignoring the clock
+    pub movetime: Option<u64>,
+}
+
+impl TimeControl {
+    pub fn is_limited(&self) -> bool {
+        self.movetime.is_some() || self.wtime.is_some() || self.btime.is_some()
+    }
+}
+
+/**
+ * Deadlines for the search: no new iteration starts after the soft one, and the search is
+ * aborted mid-iteration at the hard one
+ */
+#[derive(Debug, Clone)]
+pub struct TimeManager {
+    start: Instant,
+    soft: Option<Duration>,
+    hard: Option<Duration>,
+}
+
+impl TimeManager {
+    pub fn unlimited() -> TimeManager {
+        TimeManager { start: Instant::now(), soft: None, hard: None }
+    }
+
+    /// Starts the clock for the side to move
+    pub fn new(time: &TimeControl, side: Side) -> TimeManager {
+        let (remaining, increment) = match side {
+            Side::White => (time.wtime, time.winc),
+            Side::Black => (time.btime, time.binc),
+        };
+
+        let (soft, hard) = match (time.movetime, remaining) {
+            (Some(movetime), _) => {
+                let hard = movetime.saturating_sub(MOVE_OVERHEAD).max(1);
+                (hard, hard)
+            },
+            (None, Some(remaining)) => {
+                let available = remaining.saturating_sub(MOVE_OVERHEAD).max(1);
+                let increment = increment.unwrap_or(0);
+                let moves_to_go = time.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+                let hard = (available / moves_to_go * 3 + increment).min(available);
+                let soft = (available / moves_to_go + increment * 3 / 4).min(hard);
+                (soft, hard)
+            },
+            (None, None) => return TimeManager::unlimited(),
+        };
+
+        TimeManager {
+            start: Instant::now(),
+            soft: Some(Duration::from_millis(soft)),
+            hard: Some(Duration::from_millis(hard)),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Another iteration is unlikely to finish in time
+    pub fn soft_expired(&self) -> bool {
+        self.soft.is_some_and(|soft| self.elapsed() >= soft)
+    }
+
+    pub fn hard_expired(&self) -> bool {
+        self.hard.is_some_and(|hard| self.elapsed() >= hard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{TimeControl, TimeManager};
+    use crate::pieces::Side;
+
+    #[test]
+    fn movetime_is_used_in_full() {
+        let time = TimeManager::new(&TimeControl { movetime: Some(1000), ..TimeControl::default() }, Side::White);
+
+        assert_eq!(time.soft, Some(Duration::from_millis(950)));
+        assert_eq!(time.hard, time.soft);
+    }
+
+    #[test]
+    fn clock_is_shared_between_the_moves_to_go() {
+        let control = TimeControl {
+            wtime: Some(60_050),
+            btime: Some(1_050),
+            winc: Some(1000),
+            movestogo: Some(20),
+            ..TimeControl::default()
+        };
+
+        let white = TimeManager::new(&control, Side::White);
+        assert_eq!(white.soft, Some(Duration::from_millis(3000 + 750)));
+        assert_eq!(white.hard, Some(Duration::from_millis(9000 + 1000)));
+
+        let black = TimeManager::new(&control, Side::Black);
+        assert_eq!(black.soft, Some(Duration::from_millis(50)));
+        assert_eq!(black.hard, Some(Duration::from_millis(150)));
+    }
+
+    #[test]
+    fn last_move_before_the_time_control_can_use_everything() {
+        let control = TimeControl { btime: Some(2050), movestogo: Some(1), ..TimeControl::default() };
+        let time = TimeManager::new(&control, Side::Black);
+
+        assert_eq!(time.hard, Some(Duration::from_millis(2000)));
+        assert_eq!(time.soft, time.hard);
+    }
+
+    #[test]
+    fn no_clock_is_unlimited() {
+        let time = TimeManager::new(&TimeControl::default(), Side::White);
+
+        assert!(!time.soft_expired());
+        assert!(!time.hard_expired());
+    }
+}
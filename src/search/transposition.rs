@@ -0,0 +1,239 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::board_navigator::{CastlingSide, Move, Piece};
+use crate::score::Score;
+use crate::utils::coord_from_index;
+
+/// Which side of the search window the stored score falls on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    Lower, // failed high, the real score is at least this
+    Upper, // failed low, the real score is at most this
+}
+
+#[derive(Debug, Clone)]
+pub struct TTEntry {
+    pub key: u64,
+    pub depth: u8,
+    pub score: Score,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+}
+
+/**
+ * An entry packed into two words: the data, and the key xor'd with the data. Threads read and write
+ * the words without locking, so a slot can end up with one thread's key and another's data, but then
+ * the key no longer checks out and the slot reads as a miss
+ */
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/**
+ * Hash table of previously searched positions, indexed by zobrist key
+ *
+ * Lock free so that it can be shared between the threads of a search
+ */
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> TranspositionTable {
+        let count = (size_mb * 1024 * 1024 / std::mem::size_of::<Slot>()).max(1);
+
+        TranspositionTable {
+            slots: (0..count).map(|_| Slot::default()).collect(),
+        }
+    }
+
+    pub fn probe(&self, key: u64) -> Option<TTEntry> {
+        let slot = &self.slots[self.index(key)];
+        let data = slot.data.load(Ordering::Relaxed);
+
+        if data == 0 || slot.key.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+
+        unpack(key, data)
+    }
+
+    /// Keeps the deeper of the two entries when the same position is stored again, otherwise always replaces
+    pub fn store(&self, entry: TTEntry) {
+        let slot = &self.slots[self.index(entry.key)];
+
+        if let Some(existing) = self.probe(entry.key) {
+            if existing.depth > entry.depth {
+                return;
+            }
+        }
+
+        let data = pack(&entry);
+        slot.key.store(entry.key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    /// How full the table is in permille, sampled from the first 1000 entries as UCI does
+    pub fn hashfull(&self) -> u32 {
+        let sample = self.slots.len().min(1000);
+        let used = self.slots[..sample].iter().filter(|slot| slot.data.load(Ordering::Relaxed) != 0).count();
+        (used * 1000 / sample) as u32
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key % self.slots.len() as u64) as usize
+    }
+}
+
+// bit layout of the data word, the bound is never 0 so that an empty slot is all zeros
+const SCORE_SHIFT: u32 = 0;
+const DEPTH_SHIFT: u32 = 16;
+const BOUND_SHIFT: u32 = 24;
+const MOVE_SHIFT: u32 = 26;
+
+fn pack(entry: &TTEntry) -> u64 {
+    let bound = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+
+    (entry.score.0 as i16 as u16 as u64) << SCORE_SHIFT |
+        (entry.depth as u64) << DEPTH_SHIFT |
+        bound << BOUND_SHIFT |
+        entry.best_move.as_ref().map_or(0, pack_move) << MOVE_SHIFT
+}
+
+fn unpack(key: u64, data: u64) -> Option<TTEntry> {
+    let bound = match (data >> BOUND_SHIFT) & 0b11 {
+        1 => Bound::Exact,
+        2 => Bound::Lower,
+        3 => Bound::Upper,
+        _ => return None,
+    };
+
+    Some(TTEntry {
+        key,
+        depth: (data >> DEPTH_SHIFT) as u8,
+        score: Score((data >> SCORE_SHIFT) as u16 as i16 as i32),
+        bound,
+        best_move: unpack_move(data >> MOVE_SHIFT),
+    })
+}
+
+/// from and to squares, capture, en passant, castling and promotion, with the lowest bit marking a move
+fn pack_move(mv: &Move) -> u64 {
+    let castling = match mv.castling {
+        None => 0,
+        Some(CastlingSide::WK) => 1,
+        Some(CastlingSide::WQ) => 2,
+        Some(CastlingSide::BK) => 3,
+        Some(CastlingSide::BQ) => 4,
+    };
+    let promotion = match mv.promotion {
+        Some(Piece::N) => 1,
+        Some(Piece::B) => 2,
+        Some(Piece::R) => 3,
+        Some(Piece::Q) => 4,
+        _ => 0,
+    };
+
+    1 |
+        (mv.from.to_index() as u64) << 1 |
+        (mv.to.to_index() as u64) << 7 |
+        (mv.capture as u64) << 13 |
+        (mv.en_passant as u64) << 14 |
+        castling << 15 |
+        promotion << 18
+}
+
+fn unpack_move(bits: u64) -> Option<Move> {
+    if bits & 1 == 0 {
+        return None;
+    }
+
+    Some(Move {
+        from: coord_from_index(((bits >> 1) & 0b11_1111) as i32),
+        to: coord_from_index(((bits >> 7) & 0b11_1111) as i32),
+        capture: (bits >> 13) & 1 == 1,
+        en_passant: (bits >> 14) & 1 == 1,
+        castling: match (bits >> 15) & 0b111 {
+            1 => Some(CastlingSide::WK),
+            2 => Some(CastlingSide::WQ),
+            3 => Some(CastlingSide::BK),
+            4 => Some(CastlingSide::BQ),
+            _ => None,
+        },
+        promotion: match (bits >> 18) & 0b111 {
+            1 => Some(Piece::N),
+            2 => Some(Piece::B),
+            3 => Some(Piece::R),
+            4 => Some(Piece::Q),
+            _ => None,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::{Bound, TTEntry, TranspositionTable};
+    use crate::board_navigator::{get_legal_moves, Move};
+    use crate::fen::from_fen;
+    use crate::score::Score;
+
+    fn entry(key: u64, depth: u8) -> TTEntry {
+        TTEntry { key, depth, score: Score::DRAW, bound: Bound::Exact, best_move: None }
+    }
+
+    #[test]
+    fn probe_finds_stored_entry() {
+        let tt = TranspositionTable::new(1);
+        tt.store(entry(42, 3));
+
+        assert_eq!(tt.probe(42).map(|e| e.depth), Some(3));
+        assert!(tt.probe(43).is_none());
+    }
+
+    #[test]
+    fn store_keeps_deeper_entry_for_same_key() {
+        let tt = TranspositionTable::new(1);
+        tt.store(entry(42, 5));
+        tt.store(entry(42, 2));
+
+        assert_eq!(tt.probe(42).map(|e| e.depth), Some(5));
+    }
+
+    #[test]
+    fn entries_survive_packing() {
+        // castling, en passant, promotions and captures
+        let position = from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6");
+        let moves: Vec<Move> = get_legal_moves(&position);
+        let tt = TranspositionTable::new(1);
+
+        for (key, mv) in moves.iter().enumerate() {
+            let score = Score(-Score::MATE.0 + key as i32);
+            tt.store(TTEntry { key: key as u64, depth: 7, score, bound: Bound::Upper, best_move: Some(mv.clone()) });
+
+            let stored = tt.probe(key as u64).unwrap();
+            assert_eq!(stored.best_move.as_ref(), Some(mv));
+            assert_eq!((stored.depth, stored.score, stored.bound), (7, score, Bound::Upper));
+        }
+    }
+
+    #[test]
+    fn torn_writes_read_as_a_miss() {
+        let tt = TranspositionTable::new(1);
+        tt.store(entry(42, 3));
+
+        // another thread's data landing in between this thread's key and data
+        let slot = &tt.slots[tt.index(42)];
+        slot.data.fetch_add(1 << 16, Ordering::Relaxed);
+
+        assert!(tt.probe(42).is_none());
+    }
+}
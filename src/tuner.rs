@@ -0,0 +1,348 @@
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, Scope};
+
+use rocket::serde::json::{from_value, to_value, Value};
+
+use crate::board_navigator::{is_in_check, make_move};
+use crate::evaluator::{evaluate_with_trace, EvalParams};
+use crate::fen::try_from_fen;
+use crate::position::Position;
+use crate::search::Searcher;
+
+/**
+ * Texel tuning: finds the evaluation parameters that best predict the results of games
+ *
+ * Each position is labelled with the result of the game it was played in, the evaluation is mapped to an
+ * expected result with a sigmoid and the parameters are nudged one at a time while that lowers the mean
+ * squared error over all the positions
+ */
+pub struct LabelledPosition {
+    pub position: Position,
+    /// 1 for a white win, 0.5 for a draw and 0 for a black win
+    pub result: f64,
+}
+
+/// A FEN followed by the result, eg: `<fen> 1-0`, `<fen> [0.5]` or `<fen> c9 "0-1";`
+pub fn parse_labelled_position(line: &str) -> Result<LabelledPosition, String> {
+    let line = line.trim().trim_end_matches(';');
+    let (fen, result) = line.rsplit_once(char::is_whitespace)
+        .ok_or_else(|| String::from("Expected a FEN followed by the result"))?;
+
+    let result = match result.trim_matches(|c| c == '"' || c == '[' || c == ']') {
+        "1-0" | "1.0" | "1" => 1.0,
+        "1/2-1/2" | "0.5" => 0.5,
+        "0-1" | "0.0" | "0" => 0.0,
+        other => return Err(format!("Unknown result '{}'", other)),
+    };
+
+    let fen = fen.trim_end().trim_end_matches("c9").trim_end();
+    let position = try_from_fen(fen)?;
+
+    Ok(LabelledPosition { position, result })
+}
+
+/// One labelled position per line, blank lines and lines starting with # are skipped
+pub fn load_positions(path: &Path) -> Result<Vec<LabelledPosition>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Can't read positions from {}: {}", path.display(), e))?;
+
+    contents.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| parse_labelled_position(line).map_err(|e| format!("Line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// The position at the end of the capture sequence that the searcher's quiescence settles on, so
+/// that the static evaluation isn't thrown off by pieces that are about to be taken, or none when
+/// that sequence ends in checkmate
+pub fn quiet_position(searcher: &mut Searcher, position: &Position) -> Option<Position> {
+    let (_, pv) = searcher.quiescence(position);
+
+    let mut leaf = position.clone();
+    for mv in &pv {
+        make_move(&mut leaf, mv);
+    }
+
+    (!is_in_check(&leaf)).then_some(leaf)
+}
+
+/// Expected result for white from a centipawn evaluation, `k` scales the evaluation to the results
+pub fn sigmoid(evaluation: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * evaluation / 400.0))
+}
+
+/// Mean squared error between the results and the expected results, spread over all the cores
+pub fn mean_squared_error(positions: &[LabelledPosition], params: &EvalParams, k: f64) -> f64 {
+    thread::scope(|scope| ErrorWorkers::spawn(scope, positions).mean_squared_error(params, k))
+}
+
+/**
+ * One thread per core, each scoring its share of the positions for every set of parameters it's
+ * sent, so that the threads are started once rather than for every weight that's nudged
+ */
+struct ErrorWorkers {
+    jobs: Vec<Sender<(Arc<EvalParams>, f64)>>,
+    /// the total squared error of each worker's share, tagged with its index
+    totals: Receiver<(usize, f64)>,
+    positions: usize,
+}
+
+impl ErrorWorkers {
+    fn spawn<'scope, 'env>(scope: &'scope Scope<'scope, 'env>, positions: &'env [LabelledPosition]) -> ErrorWorkers {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = positions.len().div_ceil(threads).max(1);
+        let (total_sender, totals) = channel();
+
+        let jobs = positions.chunks(chunk_size)
+            .enumerate()
+            .map(|(index, chunk)| {
+                let (job_sender, job_receiver) = channel::<(Arc<EvalParams>, f64)>();
+                let total_sender = total_sender.clone();
+
+                // ends once the job senders are dropped
+                scope.spawn(move || {
+                    for (params, k) in job_receiver {
+                        let total = chunk.iter()
+                            .map(|labelled| {
+                                let evaluation = evaluate_with_trace(&labelled.position, &params).total as f64;
+                                (labelled.result - sigmoid(evaluation, k)).powi(2)
+                            })
+                            .sum::<f64>();
+
+                        if total_sender.send((index, total)).is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                job_sender
+            })
+            .collect();
+
+        ErrorWorkers { jobs, totals, positions: positions.len() }
+    }
+
+    fn mean_squared_error(&self, params: &EvalParams, k: f64) -> f64 {
+        if self.positions == 0 {
+            return 0.0;
+        }
+
+        let params = Arc::new(params.clone());
+        for job in &self.jobs {
+            job.send((params.clone(), k)).unwrap();
+        }
+
+        // summed in the same order every time so equal parameters always give an equal error
+        let mut totals = vec![0.0; self.jobs.len()];
+        for (index, total) in self.totals.iter().take(self.jobs.len()) {
+            totals[index] = total;
+        }
+
+        totals.iter().sum::<f64>() / self.positions as f64
+    }
+}
+
+/// The scaling constant that fits the current parameters best, so that tuning doesn't just scale them
+pub fn fit_scaling(positions: &[LabelledPosition], params: &EvalParams) -> f64 {
+    thread::scope(|scope| {
+        let workers = ErrorWorkers::spawn(scope, positions);
+        let mut k = 1.0;
+        let mut step = 0.5;
+
+        // narrows in on the minimum, the error is convex in k
+        while step > 0.001 {
+            let candidates = [k - step, k, k + step].map(|k: f64| k.max(0.01));
+            let errors = candidates.map(|k| workers.mean_squared_error(params, k));
+
+            let best = (0..3).min_by(|a, b| errors[*a].total_cmp(&errors[*b])).unwrap();
+            if best == 1 {
+                step /= 2.0;
+            }
+            k = candidates[best];
+        }
+
+        k
+    })
+}
+
+/**
+ * The parameters as a flat list of weights, so each can be nudged in turn without knowing the layout
+ */
+struct ParamVector {
+    template: Value,
+    weights: Vec<i64>,
+}
+
+impl ParamVector {
+    fn new(params: &EvalParams) -> ParamVector {
+        let template = to_value(params).unwrap();
+        let mut weights = vec![];
+        flatten(&template, &mut weights);
+
+        ParamVector { template, weights }
+    }
+
+    fn to_params(&self) -> EvalParams {
+        let mut value = self.template.clone();
+        unflatten(&mut value, &mut self.weights.iter());
+        from_value(value).unwrap()
+    }
+}
+
+fn flatten(value: &Value, weights: &mut Vec<i64>) {
+    match value {
+        Value::Number(number) => weights.extend(number.as_i64()),
+        Value::Array(values) => values.iter().for_each(|value| flatten(value, weights)),
+        Value::Object(fields) => fields.values().for_each(|value| flatten(value, weights)),
+        _ => {},
+    }
+}
+
+fn unflatten<'a>(value: &mut Value, weights: &mut impl Iterator<Item = &'a i64>) {
+    match value {
+        Value::Number(_) => *value = Value::from(*weights.next().unwrap()),
+        Value::Array(values) => values.iter_mut().for_each(|value| unflatten(value, weights)),
+        Value::Object(fields) => fields.values_mut().for_each(|value| unflatten(value, weights)),
+        _ => {},
+    }
+}
+
+/// Local search over every weight, one centipawn at a time, until a pass makes no improvement or
+/// `max_passes` is reached. `on_pass` is called with the pass number and the error after each pass.
+pub fn tune<F: FnMut(usize, f64, &EvalParams)>(
+    positions: &[LabelledPosition],
+    params: &EvalParams,
+    k: f64,
+    max_passes: usize,
+    mut on_pass: F,
+) -> EvalParams {
+    thread::scope(|scope| {
+        let workers = ErrorWorkers::spawn(scope, positions);
+        let mut vector = ParamVector::new(params);
+        let mut best_error = workers.mean_squared_error(params, k);
+
+        for pass in 1..=max_passes {
+            let mut improved = false;
+
+            for i in 0..vector.weights.len() {
+                for delta in [1, -1] {
+                    vector.weights[i] += delta;
+                    let error = workers.mean_squared_error(&vector.to_params(), k);
+
+                    if error < best_error {
+                        best_error = error;
+                        improved = true;
+                        break;
+                    }
+                    vector.weights[i] -= delta;
+                }
+            }
+
+            on_pass(pass, best_error, &vector.to_params());
+
+            if !improved {
+                break;
+            }
+        }
+
+        vector.to_params()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::{mean_squared_error, parse_labelled_position, quiet_position, sigmoid, ErrorWorkers, LabelledPosition, ParamVector};
+    use crate::board_navigator::is_in_check;
+    use crate::evaluator::EvalParams;
+    use crate::fen::{from_fen, to_fen};
+    use crate::search::Searcher;
+
+    fn searcher() -> Searcher {
+        Searcher::new(Arc::new(AtomicBool::new(false)), EvalParams::default())
+    }
+
+    #[test]
+    fn parses_common_result_formats() {
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+
+        assert_eq!(parse_labelled_position(&format!("{} 1-0", fen)).unwrap().result, 1.0);
+        assert_eq!(parse_labelled_position(&format!("{} [0.5]", fen)).unwrap().result, 0.5);
+        assert_eq!(parse_labelled_position(&format!("{} c9 \"0-1\";", fen)).unwrap().result, 0.0);
+        assert!(parse_labelled_position(&format!("{} 2-0", fen)).is_err());
+    }
+
+    #[test]
+    fn quiet_position_resolves_captures() {
+        // white wins the queen, then the position is quiet
+        let position = from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - -");
+
+        assert_eq!(to_fen(quiet_position(&mut searcher(), &position).unwrap()), "4k3/8/8/3R4/8/8/8/3K4/");
+    }
+
+    #[test]
+    fn quiet_position_skips_losing_captures() {
+        // the pawn on d5 is defended, taking it with the queen loses the queen
+        let position = from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - -");
+
+        assert_eq!(to_fen(quiet_position(&mut searcher(), &position).unwrap()), "4k3/8/2p5/3p4/8/8/8/3QK3/");
+    }
+
+    #[test]
+    fn quiet_position_is_never_in_check() {
+        let in_check = from_fen("4k3/8/8/8/8/8/8/r3K3 w - -");
+        let quiet = quiet_position(&mut searcher(), &in_check).unwrap();
+        assert!(!is_in_check(&quiet));
+
+        let mated = from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - -");
+        assert!(quiet_position(&mut searcher(), &mated).is_none());
+    }
+
+    #[test]
+    fn error_is_lower_for_matching_results() {
+        let position = from_fen("4k3/8/8/8/8/8/3Q4/4K3 w - -");
+        let win = [LabelledPosition { position: position.clone(), result: 1.0 }];
+        let loss = [LabelledPosition { position, result: 0.0 }];
+        let params = EvalParams::default();
+
+        assert!(mean_squared_error(&win, &params, 1.0) < mean_squared_error(&loss, &params, 1.0));
+        assert_eq!(sigmoid(0.0, 1.0), 0.5);
+    }
+
+    #[test]
+    fn workers_are_reused_for_each_set_of_params() {
+        let positions: Vec<_> = ["4k3/8/8/8/8/8/3Q4/4K3 w - -", "4k3/3q4/8/8/8/8/8/4K3 w - -", "4k3/8/8/8/8/8/8/4K3 w - -"]
+            .into_iter()
+            .map(|fen| LabelledPosition { position: from_fen(fen), result: 0.5 })
+            .collect();
+        let params = EvalParams::default();
+
+        thread::scope(|scope| {
+            let workers = ErrorWorkers::spawn(scope, &positions);
+
+            for k in [0.5, 1.0, 1.5] {
+                assert_eq!(workers.mean_squared_error(&params, k), mean_squared_error(&positions, &params, k));
+            }
+        });
+
+        assert_eq!(mean_squared_error(&[], &params, 1.0), 0.0);
+    }
+
+    #[test]
+    fn param_vector_round_trips() {
+        let params = EvalParams::default();
+        let mut vector = ParamVector::new(&params);
+
+        assert_eq!(vector.to_params(), params);
+
+        vector.weights[0] += 1;
+        assert_ne!(vector.to_params(), params);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::pieces::{Pieces, Side};
+use crate::position::Position;
+
+/**
+ * Random keys used to hash a position, generated at compile time so they are the same on every run
+ *
+ * [side][piece][square] for pieces, then one key per castling right, one per en passant file
+ * and one for black to move
+ */
+struct ZobristKeys {
+    pieces: [[[u64; 64]; 6]; 2],
+    castling: [u64; 4],
+    en_passant: [u64; 8],
+    black_to_move: u64,
+}
+
+const fn xorshift(mut x: u64) -> u64 {
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    x
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut keys = ZobristKeys {
+        pieces: [[[0; 64]; 6]; 2],
+        castling: [0; 4],
+        en_passant: [0; 8],
+        black_to_move: 0,
+    };
+
+    let mut side = 0;
+    while side < 2 {
+        let mut piece = 0;
+        while piece < 6 {
+            let mut square = 0;
+            while square < 64 {
+                seed = xorshift(seed);
+                keys.pieces[side][piece][square] = seed;
+                square += 1;
+            }
+            piece += 1;
+        }
+        side += 1;
+    }
+
+    let mut i = 0;
+    while i < 4 {
+        seed = xorshift(seed);
+        keys.castling[i] = seed;
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < 8 {
+        seed = xorshift(seed);
+        keys.en_passant[i] = seed;
+        i += 1;
+    }
+
+    keys.black_to_move = xorshift(seed);
+
+    keys
+}
+
+const KEYS: ZobristKeys = generate_keys();
+
+/// Hash of everything that makes a position unique for the search: pieces, side to move, castling and en passant
+pub fn zobrist_key(position: &Position) -> u64 {
+    let mut key = 0;
+
+    for (side, side_bb) in position.sides.iter().enumerate() {
+        for (piece, piece_bb) in position.pieces.iter().enumerate() {
+            for square in (*side_bb & *piece_bb).get_indicies() {
+                key ^= KEYS.pieces[side][piece][square as usize];
+            }
+        }
+    }
+
+    let castling = &position.castling;
+    for (i, right) in [castling.K, castling.Q, castling.k, castling.q].iter().enumerate() {
+        if *right {
+            key ^= KEYS.castling[i];
+        }
+    }
+
+    if let Some(en_passant_target) = &position.en_passant_target {
+        key ^= KEYS.en_passant[(en_passant_target.to_index() % 8) as usize];
+    }
+
+    if position.active_colour == Side::Black {
+        key ^= KEYS.black_to_move;
+    }
+
+    key
+}
+
+/// Hash of the pawns alone, for caching pawn structure evaluation
+pub fn pawn_key(position: &Position) -> u64 {
+    let mut key = 0;
+
+    for (side, side_bb) in position.sides.iter().enumerate() {
+        for square in (*side_bb & position.pieces[Pieces::PAWN]).get_indicies() {
+            key ^= KEYS.pieces[side][Pieces::PAWN][square as usize];
+        }
+    }
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pawn_key, zobrist_key};
+    use crate::fen::from_fen;
+
+    #[test]
+    fn same_position_same_key() {
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3";
+        assert_eq!(zobrist_key(&from_fen(fen)), zobrist_key(&from_fen(fen)));
+    }
+
+    #[test]
+    fn side_to_move_changes_key() {
+        let white = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+        let black = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq -");
+        assert_ne!(zobrist_key(&white), zobrist_key(&black));
+    }
+
+    #[test]
+    fn pawn_key_ignores_other_pieces() {
+        let before = from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -");
+        let after = from_fen("rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq -");
+        let pushed = from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3");
+
+        assert_eq!(pawn_key(&before), pawn_key(&after));
+        assert_ne!(pawn_key(&before), pawn_key(&pushed));
+    }
+}